@@ -0,0 +1,186 @@
+use crate::diagnostics::Span;
+use std::fmt;
+
+/// An RC expression: IDs, coordinates and style bit sets such as
+/// `WS_CHILD | WS_VISIBLE | NOT WS_TABSTOP`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(i64),
+    Symbol(String),
+    String(String),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    BitNot,
+    /// The RC `NOT` keyword, which clears bits from a style.
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Symbol(name) => f.write_str(name),
+            Expr::String(text) => write!(f, "\"{}\"", text.replace('"', "\"\"")),
+            Expr::Unary(UnaryOp::Neg, e) => write!(f, "-{}", e),
+            Expr::Unary(UnaryOp::BitNot, e) => write!(f, "~{}", e),
+            Expr::Unary(UnaryOp::Not, e) => write!(f, "NOT {}", e),
+            Expr::Binary(op, lhs, rhs) => {
+                let op = match op {
+                    BinaryOp::Or => "|",
+                    BinaryOp::And => "&",
+                    BinaryOp::Add => "+",
+                    BinaryOp::Sub => "-",
+                    BinaryOp::Mul => "*",
+                    BinaryOp::Div => "/",
+                };
+                write!(f, "{} {} {}", lhs, op, rhs)
+            }
+        }
+    }
+}
+
+/// A keyword followed by comma-separated arguments, e.g. a dialog control,
+/// an optional statement such as `CAPTION "About"`, or a VERSIONINFO value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub keyword: String,
+    pub args: Vec<Expr>,
+    pub span: Span,
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.keyword)?;
+        for (i, arg) in self.args.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " " } else { ", " }, arg)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Script {
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug)]
+pub enum Item {
+    Language {
+        primary: Expr,
+        sub: Expr,
+        span: Span,
+    },
+    Resource(ResourceDef),
+}
+
+#[derive(Debug)]
+pub struct ResourceDef {
+    /// `None` for STRINGTABLE, which has no ID of its own.
+    pub id: Option<Expr>,
+    /// Upper-cased type keyword (`DIALOGEX`, `ICON`, ...) or user-defined type.
+    pub type_name: String,
+    pub memory_flags: Vec<String>,
+    /// Optional statements between the header and BEGIN (CAPTION, STYLE, ...).
+    pub options: Vec<Statement>,
+    pub body: ResourceBody,
+    /// Span of the resource ID, or of the STRINGTABLE keyword.
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum ResourceBody {
+    File(String),
+    Data(Vec<Expr>),
+    StringTable(Vec<StringEntry>),
+    Accelerators(Vec<Accelerator>),
+    Menu(Vec<MenuItem>),
+    Dialog(DialogDef),
+    Toolbar(ToolbarDef),
+    VersionInfo(VersionInfoDef),
+}
+
+#[derive(Debug)]
+pub struct StringEntry {
+    pub id: Expr,
+    pub text: String,
+    pub span: Span,
+}
+
+/// One ACCELERATORS entry: key, command ID and options.
+#[derive(Debug)]
+pub struct Accelerator {
+    pub args: Vec<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum MenuItem {
+    Separator {
+        span: Span,
+    },
+    Item {
+        text: String,
+        args: Vec<Expr>,
+        span: Span,
+    },
+    Popup {
+        text: String,
+        args: Vec<Expr>,
+        items: Vec<MenuItem>,
+        span: Span,
+    },
+}
+
+impl MenuItem {
+    pub fn span(&self) -> Span {
+        match self {
+            MenuItem::Separator { span }
+            | MenuItem::Item { span, .. }
+            | MenuItem::Popup { span, .. } => *span,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DialogDef {
+    /// x, y, cx, cy and, for DIALOGEX, an optional help ID.
+    pub params: Vec<Expr>,
+    pub controls: Vec<Statement>,
+}
+
+#[derive(Debug)]
+pub struct ToolbarDef {
+    pub width: Expr,
+    pub height: Expr,
+    /// Button IDs in order; `None` is a SEPARATOR.
+    pub buttons: Vec<Option<Expr>>,
+}
+
+#[derive(Debug)]
+pub struct VersionInfoDef {
+    pub fixed: Vec<Statement>,
+    pub blocks: Vec<VersionBlock>,
+}
+
+#[derive(Debug)]
+pub struct VersionBlock {
+    pub name: String,
+    pub values: Vec<Statement>,
+    pub blocks: Vec<VersionBlock>,
+    pub span: Span,
+}
@@ -0,0 +1,209 @@
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// A range in an original source file, before preprocessing: index into the
+/// `SourceMap`, 1-based line and column, and length in characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+    pub file: usize,
+    pub line: usize,
+    pub column: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+#[derive(Debug)]
+struct SourceFile {
+    path: PathBuf,
+    lines: Vec<String>,
+}
+
+/// Every file read while parsing, kept so diagnostics can quote it.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn add_file(&mut self, path: &Path) -> usize {
+        self.files.push(SourceFile {
+            path: path.to_path_buf(),
+            lines: Vec::new(),
+        });
+        self.files.len() - 1
+    }
+
+    pub fn push_line(&mut self, file: usize, line: &str) {
+        self.files[file].lines.push(line.to_string());
+    }
+
+    pub fn path(&self, file: usize) -> &Path {
+        &self.files[file].path
+    }
+
+    pub fn line(&self, file: usize, line: usize) -> Option<&str> {
+        self.files.get(file)?.lines.get(line.checked_sub(1)?).map(String::as_str)
+    }
+}
+
+/// Errors and warnings collected over a whole run, with the sources they point into.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    pub items: Vec<Diagnostic>,
+    pub sources: SourceMap,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Diagnostics::default()
+    }
+
+    pub fn push(&mut self, severity: Severity, span: Option<Span>, message: impl Into<String>) {
+        self.items.push(Diagnostic {
+            severity,
+            message: message.into(),
+            span,
+        });
+    }
+
+    pub fn error(&mut self, span: Option<Span>, message: impl Into<String>) {
+        self.push(Severity::Error, span, message);
+    }
+
+    pub fn warning(&mut self, span: Option<Span>, message: impl Into<String>) {
+        self.push(Severity::Warning, span, message);
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.items.iter().filter(|d| d.severity == severity).count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+
+    /// Formats all diagnostics like rustc does, quoting the offending line,
+    /// followed by a summary line.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for diagnostic in &self.items {
+            self.render_one(diagnostic, &mut out);
+        }
+
+        let errors = self.count(Severity::Error);
+        let warnings = self.count(Severity::Warning);
+        let warnings_text = format!("{} warning{} emitted", warnings, plural(warnings));
+        if errors > 0 {
+            let _ = write!(out, "error: aborting due to {} previous error{}", errors, plural(errors));
+            if warnings > 0 {
+                let _ = write!(out, "; {}", warnings_text);
+            }
+            out.push('\n');
+        } else if warnings > 0 {
+            let _ = writeln!(out, "warning: {}", warnings_text);
+        }
+        out
+    }
+
+    fn render_one(&self, diagnostic: &Diagnostic, out: &mut String) {
+        let _ = writeln!(out, "{}: {}", diagnostic.severity, diagnostic.message);
+        let Some(span) = diagnostic.span.filter(|s| s.file < self.sources.files.len()) else {
+            out.push('\n');
+            return;
+        };
+
+        let gutter = " ".repeat(span.line.to_string().len());
+        let _ = writeln!(
+            out,
+            "{}--> {}:{}:{}",
+            gutter,
+            self.sources.path(span.file).display(),
+            span.line,
+            span.column
+        );
+        if let Some(text) = self.sources.line(span.file, span.line) {
+            // Tabs are expanded so the carets stay under the quoted text.
+            let prefix: String = text.chars().take(span.column.saturating_sub(1)).collect();
+            let _ = writeln!(out, "{} |", gutter);
+            let _ = writeln!(out, "{} | {}", span.line, text.replace('\t', "    "));
+            let _ = writeln!(
+                out,
+                "{} | {}{}",
+                gutter,
+                " ".repeat(prefix.replace('\t', "    ").chars().count()),
+                "^".repeat(span.len.max(1))
+            );
+        }
+        out.push('\n');
+    }
+}
+
+fn plural(n: usize) -> &'static str {
+    if n == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_quotes_the_line_under_the_span() {
+        let mut diagnostics = Diagnostics::new();
+        let file = diagnostics.sources.add_file(Path::new("app.rc"));
+        diagnostics.sources.push_line(file, "IDD_MAIN DIALOG 0, 0, 10, 10");
+        diagnostics.sources.push_line(file, "\tCAPTION 12");
+        let span = Span {
+            file,
+            line: 2,
+            column: 10,
+            len: 2,
+        };
+        diagnostics.error(Some(span), "expected a string");
+        assert_eq!(
+            diagnostics.render(),
+            "error: expected a string\n \
+             --> app.rc:2:10\n  \
+             |\n\
+             2 |     CAPTION 12\n  \
+             |             ^^\n\
+             \n\
+             error: aborting due to 1 previous error\n"
+        );
+    }
+
+    #[test]
+    fn render_summarises_errors_and_warnings() {
+        let mut diagnostics = Diagnostics::new();
+        assert_eq!(diagnostics.render(), "");
+        diagnostics.warning(None, "first");
+        diagnostics.warning(None, "second");
+        assert!(diagnostics.render().ends_with("warning: first\n\nwarning: second\n\nwarning: 2 warnings emitted\n"));
+        diagnostics.error(None, "third");
+        assert!(diagnostics.render().ends_with("error: aborting due to 1 previous error; 2 warnings emitted\n"));
+    }
+}
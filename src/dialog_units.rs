@@ -0,0 +1,169 @@
+use crate::resource::RcFont;
+use log::info;
+
+// Average character width and height in pixels, at 96 DPI, of the fonts
+// dialogs commonly use: face, point size, width, height.
+const BUILTIN_METRICS: &[(&str, i32, i32, i32)] = &[
+    ("MS Shell Dlg", 8, 6, 13),
+    ("MS Shell Dlg 2", 8, 6, 13),
+    ("MS Sans Serif", 8, 6, 13),
+    ("Microsoft Sans Serif", 8, 6, 13),
+    ("Tahoma", 8, 6, 13),
+    ("Segoe UI", 9, 7, 15),
+    ("MS Sans Serif", 10, 7, 16),
+    ("Arial", 8, 6, 14),
+    ("Verdana", 8, 7, 13),
+    ("Courier New", 8, 7, 14),
+    ("MS UI Gothic", 9, 6, 12),
+];
+
+// Base units of the system font, used by dialogs without a FONT statement.
+const SYSTEM_FONT_UNITS: BaseUnits = BaseUnits { x: 8, y: 16 };
+
+/// Dialog base units: the average character width and height of the dialog
+/// font. A dialog unit is a quarter of the width or an eighth of the height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaseUnits {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl BaseUnits {
+    pub fn to_pixels_x(self, units: i32) -> i32 {
+        mul_div(units, self.x, 4)
+    }
+
+    pub fn to_pixels_y(self, units: i32) -> i32 {
+        mul_div(units, self.y, 8)
+    }
+}
+
+// Like the Win32 MulDiv used by MapDialogRect: rounds half away from zero.
+fn mul_div(value: i32, numerator: i32, denominator: i32) -> i32 {
+    (value as f64 * numerator as f64 / denominator as f64).round() as i32
+}
+
+/// Character sizes of known fonts, used to convert dialog units to pixels.
+#[derive(Debug, Clone)]
+pub struct FontMetrics {
+    table: Vec<(String, i32, BaseUnits)>,
+}
+
+impl Default for FontMetrics {
+    fn default() -> Self {
+        let mut metrics = FontMetrics { table: Vec::new() };
+        for (face, point_size, x, y) in BUILTIN_METRICS {
+            metrics.insert(face, *point_size, BaseUnits { x: *x, y: *y });
+        }
+        metrics
+    }
+}
+
+impl FontMetrics {
+    /// Adds or replaces the measurements of a font.
+    pub fn insert(&mut self, face: &str, point_size: i32, units: BaseUnits) {
+        self.table
+            .retain(|(f, p, _)| !(f.eq_ignore_ascii_case(face) && *p == point_size));
+        self.table.push((face.to_string(), point_size, units));
+    }
+
+    /// Base units of a dialog using `font`. Fonts missing from the table are
+    /// estimated from their point size.
+    pub fn base_units(&self, font: Option<&RcFont>) -> BaseUnits {
+        let Some(font) = font else {
+            return SYSTEM_FONT_UNITS;
+        };
+        let known = self
+            .table
+            .iter()
+            .find(|(face, size, _)| face.eq_ignore_ascii_case(&font.face) && *size == font.point_size);
+        if let Some((_, _, units)) = known {
+            return *units;
+        }
+
+        // Cell height at 96 DPI including internal leading, and the typical
+        // average width of a proportional UI font.
+        let height = (font.point_size as f64 * 96.0 / 72.0 * 1.22).round().max(1.0);
+        let units = BaseUnits {
+            x: (height * 0.46).round() as i32,
+            y: height as i32,
+        };
+        info!(
+            "No metrics for font \"{}\" {}pt, estimating {}x{} base units",
+            font.face, font.point_size, units.x, units.y
+        );
+        units
+    }
+}
+
+/// Parses a `FACE,POINTS,WIDTH,HEIGHT` font metrics specification, as given
+/// on the command line.
+pub fn parse_font_metric(spec: &str) -> Result<(String, i32, BaseUnits), String> {
+    let parts: Vec<&str> = spec.rsplitn(4, ',').collect();
+    let [y, x, point_size, face] = parts[..] else {
+        return Err(format!("expected FACE,POINTS,WIDTH,HEIGHT, found '{}'", spec));
+    };
+    let number = |text: &str| {
+        text.trim()
+            .parse::<i32>()
+            .map_err(|_| format!("'{}' is not a number in '{}'", text, spec))
+    };
+    Ok((
+        face.trim().to_string(),
+        number(point_size)?,
+        BaseUnits {
+            x: number(x)?,
+            y: number(y)?,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font(face: &str, point_size: i32) -> RcFont {
+        RcFont {
+            point_size,
+            face: face.to_string(),
+            weight: 0,
+            italic: false,
+            charset: 0,
+        }
+    }
+
+    #[test]
+    fn base_units_of_known_and_unknown_fonts() {
+        let metrics = FontMetrics::default();
+        assert_eq!(metrics.base_units(Some(&font("ms shell dlg", 8))), BaseUnits { x: 6, y: 13 });
+        assert_eq!(metrics.base_units(Some(&font("Segoe UI", 9))), BaseUnits { x: 7, y: 15 });
+        assert_eq!(metrics.base_units(None), SYSTEM_FONT_UNITS);
+        // 12pt is 16px, with leading 20px high and about 9px wide.
+        assert_eq!(metrics.base_units(Some(&font("Unknown", 12))), BaseUnits { x: 9, y: 20 });
+    }
+
+    #[test]
+    fn inserted_metrics_replace_the_builtin_ones() {
+        let mut metrics = FontMetrics::default();
+        metrics.insert("MS SHELL DLG", 8, BaseUnits { x: 5, y: 12 });
+        assert_eq!(metrics.base_units(Some(&font("MS Shell Dlg", 8))), BaseUnits { x: 5, y: 12 });
+    }
+
+    #[test]
+    fn dialog_units_round_like_map_dialog_rect() {
+        let units = BaseUnits { x: 6, y: 13 };
+        assert_eq!((units.to_pixels_x(100), units.to_pixels_y(100)), (150, 163));
+        assert_eq!((units.to_pixels_x(1), units.to_pixels_y(1)), (2, 2));
+        assert_eq!(units.to_pixels_x(-1), -2);
+    }
+
+    #[test]
+    fn font_metric_specifications() {
+        let (face, size, units) = parse_font_metric("Segoe UI, 10, 8, 17").unwrap();
+        assert_eq!((face.as_str(), size, units), ("Segoe UI", 10, BaseUnits { x: 8, y: 17 }));
+        // The face may itself contain commas.
+        assert_eq!(parse_font_metric("A,B,9,7,15").unwrap().0, "A,B");
+        assert!(parse_font_metric("Tahoma,8,6").is_err());
+        assert!(parse_font_metric("Tahoma,8,six,13").is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Xml(xml::writer::Error),
+    /// The script has errors. They are recorded in the `Diagnostics` passed
+    /// to the parser.
+    Parse { errors: usize },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Xml(e) => write!(f, "cannot write XML: {}", e),
+            Error::Parse { errors } => write!(
+                f,
+                "the script has {} error{}",
+                errors,
+                if *errors == 1 { "" } else { "s" }
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Xml(e) => Some(e),
+            Error::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<xml::writer::Error> for Error {
+    fn from(e: xml::writer::Error) -> Self {
+        Error::Xml(e)
+    }
+}
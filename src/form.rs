@@ -0,0 +1,1046 @@
+use crate::diagnostics::Diagnostics;
+use crate::dialog_units::{BaseUnits, FontMetrics};
+use crate::generator::QRC_FILE_NAME;
+use crate::layout::{infer_layout, ALIGN_TOLERANCE};
+use crate::resource::{DialogControl, RcDialogEx, RcDialogInit, RcId, ResourceScript};
+use crate::styles::{
+    BS_AUTORADIOBUTTON, BS_DEFPUSHBUTTON, BS_PUSHBUTTON, BS_TYPEMASK, DS_CONTEXTHELP, DS_MODALFRAME, DS_SYSMODAL,
+    SS_NOPREFIX, WS_BORDER, WS_CAPTION, WS_CHILD, WS_DISABLED, WS_DLGFRAME, WS_EX_CONTEXTHELP, WS_EX_LAYOUTRTL, WS_EX_TOOLWINDOW,
+    WS_EX_TOPMOST, WS_GROUP, WS_MAXIMIZEBOX, WS_MINIMIZEBOX, WS_SYSMENU, WS_TABSTOP, WS_THICKFRAME,
+};
+use crate::ui::{UiConnection, UiCustomWidget, UiForm, UiValue, UiWidget};
+use crate::widgets::{buddy_spin_box, control_widget, is_spin_buddy, mnemonic, set_window_styles};
+use log::info;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+// Trailing words split off a single-word ID so that `IDD_ABOUTBOX` becomes
+// `AboutBox` rather than `Aboutbox`.
+const NAME_SUFFIXES: &[&str] = &[
+    "BOX", "DIALOG", "DLG", "PAGE", "VIEW", "BAR", "WIZARD", "SHEET", "PANE", "PANEL", "FORM", "WND",
+];
+
+// Words that cannot be used as widget names in the code uic generates.
+const CPP_KEYWORDS: &[&str] = &[
+    "auto", "bool", "break", "case", "catch", "char", "class", "const", "continue", "default",
+    "delete", "do", "double", "else", "enum", "explicit", "extern", "false", "float", "for",
+    "friend", "goto", "if", "inline", "int", "long", "namespace", "new", "operator", "private",
+    "protected", "public", "return", "short", "signed", "sizeof", "static", "struct", "switch",
+    "template", "this", "throw", "true", "try", "typedef", "union", "unsigned", "using",
+    "virtual", "void", "volatile", "while",
+];
+
+/// Settings for converting dialogs into Designer forms.
+#[derive(Debug, Clone, Default)]
+pub struct FormOptions {
+    pub naming: UiNaming,
+    pub font_metrics: FontMetrics,
+    pub promotion: PromotionRule,
+    /// Arranges the controls in layouts inferred from their positions,
+    /// instead of placing them by their geometry.
+    pub layouts: bool,
+}
+
+/// How the Qt class and header of a promoted widget are named. Controls of
+/// window classes without a Qt counterpart become such placeholders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromotionRule {
+    /// Qt class of a window class, where `{class}` stands for the window
+    /// class, e.g. `Q{class}`.
+    pub class: String,
+    /// Header declaring a Qt class, where `{class}` stands for the class and
+    /// `{lower}` for the class in lowercase.
+    pub header: String,
+}
+
+impl Default for PromotionRule {
+    fn default() -> Self {
+        PromotionRule {
+            class: "{class}".to_string(),
+            header: "{lower}.h".to_string(),
+        }
+    }
+}
+
+impl PromotionRule {
+    pub fn class_name(&self, window_class: &str) -> String {
+        let window_class: String = window_class
+            .trim_matches('"')
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let class = self.class.replace("{class}", &window_class);
+        if class.is_empty() || class.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("Custom{}", class)
+        } else {
+            class
+        }
+    }
+
+    pub fn header(&self, class: &str) -> String {
+        self.header
+            .replace("{class}", class)
+            .replace("{lower}", &class.to_lowercase())
+    }
+}
+
+/// How dialog IDs are turned into form class and .ui file names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UiNaming {
+    /// `IDD_ABOUTBOX` → class `AboutBox` in `AboutBox.ui`.
+    #[default]
+    CamelCase,
+    /// `IDD_ABOUTBOX` → class `AboutBox` in `aboutbox.ui`, as Qt Creator names forms.
+    Lowercase,
+    /// `IDD_ABOUTBOX` → class `IDD_ABOUTBOX` in `IDD_ABOUTBOX.ui`.
+    Id,
+}
+
+impl UiNaming {
+    pub fn class_name(self, id: &RcId) -> String {
+        let name = id.name.trim_matches('"');
+        let class = match self {
+            UiNaming::Id => name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect(),
+            UiNaming::CamelCase | UiNaming::Lowercase => {
+                let name = name
+                    .strip_prefix("IDD_")
+                    .or_else(|| name.strip_prefix("IDD"))
+                    .unwrap_or(name);
+                name.split(|c: char| !c.is_ascii_alphanumeric())
+                    .flat_map(split_suffix)
+                    .map(capitalize)
+                    .collect::<String>()
+            }
+        };
+        if class.is_empty() || class.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("Dialog{}", class)
+        } else {
+            class
+        }
+    }
+
+    pub fn file_name(self, class_name: &str) -> String {
+        match self {
+            UiNaming::Lowercase => format!("{}.ui", class_name.to_lowercase()),
+            UiNaming::CamelCase | UiNaming::Id => format!("{}.ui", class_name),
+        }
+    }
+}
+
+impl FromStr for UiNaming {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "camel" => Ok(UiNaming::CamelCase),
+            "lower" => Ok(UiNaming::Lowercase),
+            "id" => Ok(UiNaming::Id),
+            _ => Err(format!("unknown naming scheme '{}', expected camel, lower or id", s)),
+        }
+    }
+}
+
+fn split_suffix(word: &str) -> Vec<&str> {
+    for suffix in NAME_SUFFIXES {
+        if word.len() > suffix.len() && word.to_ascii_uppercase().ends_with(suffix) {
+            let (head, tail) = word.split_at(word.len() - suffix.len());
+            return vec![head, tail];
+        }
+    }
+    vec![word]
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase(),
+        None => String::new(),
+    }
+}
+
+/// Hands out unique widget names within one form.
+#[derive(Debug, Default)]
+pub(crate) struct WidgetNames {
+    used: HashSet<String>,
+}
+
+impl WidgetNames {
+    // `IDC_USER_NAME` becomes `userName`. Controls without a usable ID are
+    // named after their Qt class, like Designer does: `label`, `label_2`...
+    pub(crate) fn name_for(&mut self, id: &RcId, qt_class: &str) -> String {
+        let base = qt_class.trim_start_matches('Q');
+        let base = base[..1].to_ascii_lowercase() + &base[1..];
+
+        let symbol = if id.value == Some(-1) || id.name.parse::<i64>().is_ok() {
+            ""
+        } else {
+            let name = id.name.as_str();
+            name.strip_prefix("IDC_")
+                .or_else(|| name.strip_prefix("ID_"))
+                .or_else(|| name.strip_prefix("ID"))
+                .unwrap_or(name)
+        };
+        let mut name = String::new();
+        for word in symbol.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()) {
+            if name.is_empty() {
+                name.push_str(&word.to_ascii_lowercase());
+            } else {
+                name.push_str(&capitalize(word));
+            }
+        }
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            name = base.clone();
+        } else if CPP_KEYWORDS.contains(&name.as_str()) {
+            name.push_str(&(base[..1].to_ascii_uppercase() + &base[1..]));
+        }
+
+        self.unique(&name)
+    }
+
+    // `name`, or `name_2`, `name_3`... if it is taken.
+    pub(crate) fn unique(&mut self, name: &str) -> String {
+        let mut unique = name.to_string();
+        let mut n = 2;
+        while !self.used.insert(unique.clone()) {
+            unique = format!("{}_{}", name, n);
+            n += 1;
+        }
+        unique
+    }
+}
+
+// State shared by the conversion of the controls of one dialog.
+pub(crate) struct FormContext<'a> {
+    pub(crate) script: &'a ResourceScript,
+    pub(crate) units: BaseUnits,
+    pub(crate) names: WidgetNames,
+    pub(crate) diagnostics: &'a mut Diagnostics,
+    // DLGINIT data of the dialog.
+    pub(crate) init: Option<&'a RcDialogInit>,
+    pub(crate) custom_widgets: Vec<UiCustomWidget>,
+    pub(crate) promotion: &'a PromotionRule,
+    // Set once a widget shows an image from the .qrc file.
+    pub(crate) uses_resources: bool,
+}
+
+/// Converts a dialog into a Designer form named `class_name`. Dialog units
+/// are converted to pixels using the dialog font; images shown by static
+/// controls are looked up in `script`. `init` is the dialog's DLGINIT data.
+pub fn dialog_form(
+    dialog: &RcDialogEx,
+    init: Option<&RcDialogInit>,
+    class_name: &str,
+    script: &ResourceScript,
+    options: &FormOptions,
+    diagnostics: &mut Diagnostics,
+) -> UiForm {
+    let mut cx = FormContext {
+        script,
+        units: options.font_metrics.base_units(dialog.font.as_ref()),
+        names: WidgetNames::default(),
+        diagnostics,
+        init,
+        custom_widgets: Vec::new(),
+        promotion: &options.promotion,
+        uses_resources: false,
+    };
+    cx.names.used.insert(class_name.to_string());
+
+    let mut widget = UiWidget::new("QDialog", class_name);
+    widget.set(
+        "geometry",
+        UiValue::Rect {
+            x: 0,
+            y: 0,
+            width: cx.units.to_pixels_x(dialog.width),
+            height: cx.units.to_pixels_y(dialog.height),
+        },
+    );
+    if !dialog.caption.is_empty() {
+        widget.set("windowTitle", UiValue::String(dialog.caption.clone()));
+    }
+    set_dialog_styles(&mut widget, dialog);
+
+    // Automatic radio buttons are exclusive within a group, which runs from
+    // one WS_GROUP control to the next. Each group becomes a button group
+    // named after its first radio button.
+    let mut children = Vec::new();
+    let mut button_groups = Vec::new();
+    let mut group_start = 0;
+    let mut group_name: Option<(usize, String)> = None;
+    let mut controls = dialog.controls.iter().enumerate().peekable();
+    while let Some((index, control)) = controls.next() {
+        if control.effective_style & WS_GROUP != 0 {
+            group_start = index;
+        }
+        let mut child = match controls.next_if(|(_, next)| is_spin_buddy(control, next)) {
+            Some((up_index, up_down)) => {
+                // The up-down may start the group of the controls after it.
+                if up_down.effective_style & WS_GROUP != 0 {
+                    group_start = up_index;
+                }
+                buddy_spin_box(control, up_down, &mut cx)
+            }
+            None => control_widget(control, &mut cx),
+        };
+        set_window_styles(&mut child, control);
+        if is_auto_radio_button(control) {
+            let name = match &group_name {
+                Some((start, name)) if *start == group_start => name.clone(),
+                _ => {
+                    let id = RcId {
+                        name: format!("{}_GROUP", control.id.name),
+                        ..control.id.clone()
+                    };
+                    let name = cx.names.name_for(&id, "QButtonGroup");
+                    button_groups.push(name.clone());
+                    group_name = Some((group_start, name.clone()));
+                    name
+                }
+            };
+            child.set_attribute("buttonGroup", UiValue::String(name));
+        }
+        children.push((child, control));
+    }
+    let mut connections = Vec::new();
+    if let Some(button_box) = dialog_button_box(&mut children, &mut cx.names) {
+        for (signal, slot) in [("accepted()", "accept()"), ("rejected()", "reject()")] {
+            connections.push(UiConnection {
+                sender: button_box.clone(),
+                signal: signal.to_string(),
+                receiver: class_name.to_string(),
+                slot: slot.to_string(),
+            });
+        }
+    }
+
+    // The tab order is the order of the controls with WS_TABSTOP. The other
+    // controls take no focus, except radio buttons reached with the arrow
+    // keys from the tab stop of their group.
+    let mut tab_stops = Vec::new();
+    for (child, control) in &mut children {
+        if child.class == "QDialogButtonBox" {
+            continue;
+        }
+        let is_static = control.class.eq_ignore_ascii_case("Static");
+        if control.effective_style & WS_TABSTOP != 0 && !is_static {
+            tab_stops.push(child.name.clone());
+        } else if is_static || !is_auto_radio_button(control) {
+            child.set("focusPolicy", UiValue::Enum("Qt::NoFocus".into()));
+        }
+    }
+
+    // A label with a mnemonic gives the focus to the next control in tab
+    // order. QLabel only shows the mnemonic of a label with a buddy.
+    for i in 0..children.len() {
+        let (label, control) = &children[i];
+        if label.class != "QLabel"
+            || !control.class.eq_ignore_ascii_case("Static")
+            || control.effective_style & SS_NOPREFIX != 0
+            || mnemonic(&control.text).is_none()
+        {
+            continue;
+        }
+        let buddy = children[i + 1..].iter().map(|(w, _)| &w.name).find(|name| tab_stops.contains(name));
+        if let Some(buddy) = buddy.cloned() {
+            let text = control.text.clone();
+            let label = &mut children[i].0;
+            label.set("buddy", UiValue::CString(buddy));
+            label.set("text", UiValue::String(text));
+        }
+    }
+    check_mnemonics(dialog, cx.diagnostics);
+    widget.children = nest_in_group_boxes(children, cx.diagnostics);
+    if options.layouts {
+        infer_layout(&mut widget, &mut cx.names);
+    }
+
+    UiForm {
+        class: class_name.to_string(),
+        widget,
+        custom_widgets: cx.custom_widgets,
+        tab_stops,
+        resources: if cx.uses_resources {
+            vec![QRC_FILE_NAME.to_string()]
+        } else {
+            Vec::new()
+        },
+        connections,
+        button_groups,
+    }
+}
+
+// Push buttons with a standard ID that become one QDialogButtonBox, with
+// the standard button and its usual text. The IDs are IDOK to IDHELP from
+// winuser.h and ID_APPLY_NOW and ID_HELP from afxres.h.
+const STANDARD_BUTTONS: &[(i64, &str, &str)] = &[
+    (1, "Ok", "OK"),
+    (2, "Cancel", "Cancel"),
+    (3, "Abort", "Abort"),
+    (4, "Retry", "Retry"),
+    (5, "Ignore", "Ignore"),
+    (6, "Yes", "Yes"),
+    (7, "No", "No"),
+    (8, "Close", "Close"),
+    (9, "Help", "Help"),
+    (0x3021, "Apply", "Apply"),
+    (0xE146, "Help", "Help"),
+];
+
+// Replaces the push buttons with standard IDs by a QDialogButtonBox when
+// they are lined up in a row or a column with nothing else in between.
+// Returns the name of the button box.
+fn dialog_button_box(children: &mut Vec<(UiWidget, &DialogControl)>, names: &mut WidgetNames) -> Option<String> {
+    let standard = |(widget, control): &(UiWidget, &DialogControl)| {
+        let push = matches!(control.effective_style & BS_TYPEMASK, BS_PUSHBUTTON | BS_DEFPUSHBUTTON);
+        let button = STANDARD_BUTTONS.iter().find(|(id, _, _)| Some(*id) == control.id.value);
+        button.filter(|_| push && widget.class == "QPushButton")
+    };
+    let members: Vec<usize> = (0..children.len()).filter(|&i| standard(&children[i]).is_some()).collect();
+    let buttons: Vec<_> = members.iter().map(|&i| standard(&children[i]).unwrap()).collect();
+    // Two buttons for the same standard button, such as IDHELP and ID_HELP,
+    // cannot share a box.
+    let same_button = |i: usize| buttons[..i].iter().any(|b| b.1 == buttons[i].1);
+    if members.is_empty() || (1..buttons.len()).any(same_button) {
+        return None;
+    }
+
+    let rects: Vec<Rect> = members.iter().map(|&i| widget_rect(&children[i].0)).collect();
+    let aligned = |coordinate: fn(&Rect) -> i32| rects.iter().all(|r| (coordinate(r) - coordinate(&rects[0])).abs() <= ALIGN_TOLERANCE);
+    let vertical = match (aligned(|r| r.1), aligned(|r| r.0)) {
+        (true, _) => false,
+        (false, true) => true,
+        (false, false) => {
+            info!("Keeping the standard buttons, which are not lined up");
+            return None;
+        }
+    };
+    let x = rects.iter().map(|r| r.0).min().unwrap();
+    let y = rects.iter().map(|r| r.1).min().unwrap();
+    let area = (
+        x,
+        y,
+        rects.iter().map(|r| r.0 + r.2).max().unwrap() - x,
+        rects.iter().map(|r| r.1 + r.3).max().unwrap() - y,
+    );
+    let in_between = (0..children.len()).any(|i| {
+        let rect = widget_rect(&children[i].0);
+        !members.contains(&i) && overlaps(rect, area) && !contains(rect, area)
+    });
+    if in_between {
+        info!("Keeping the standard buttons, which have other controls between them");
+        return None;
+    }
+
+    let mut button_box = UiWidget::new("QDialogButtonBox", &names.unique("buttonBox"));
+    button_box.set(
+        "geometry",
+        UiValue::Rect {
+            x: area.0,
+            y: area.1,
+            width: area.2,
+            height: area.3,
+        },
+    );
+    let orientation = if vertical { "Qt::Vertical" } else { "Qt::Horizontal" };
+    button_box.set("orientation", UiValue::Enum(orientation.into()));
+    let flags: Vec<String> = STANDARD_BUTTONS
+        .iter()
+        .filter(|button| buttons.contains(button))
+        .map(|(_, name, _)| format!("QDialogButtonBox::{}", name))
+        .collect();
+    button_box.set("standardButtons", UiValue::Set(flags.join("|")));
+
+    // Standard buttons show Qt's text; other labels are left to the developer.
+    let renamed: Vec<String> = members
+        .iter()
+        .zip(&buttons)
+        .filter(|(&i, (_, _, text))| !children[i].1.text.replace('&', "").eq_ignore_ascii_case(text))
+        .map(|(&i, (_, name, _))| format!("{} was \"{}\"", name, children[i].1.text))
+        .collect();
+    if !renamed.is_empty() {
+        button_box.comment = Some(format!("TODO: relabel the buttons: {}", renamed.join(", ")));
+    }
+
+    let control = children[members[0]].1;
+    for &i in members.iter().rev() {
+        children.remove(i);
+    }
+    let name = button_box.name.clone();
+    children.insert(members[0], (button_box, control));
+    Some(name)
+}
+
+// Properties and window flags of the dialog itself from its STYLE and
+// EXSTYLE. Window flags are only set when the title bar differs from the
+// one Qt gives a dialog.
+fn set_dialog_styles(widget: &mut UiWidget, dialog: &RcDialogEx) {
+    let style = dialog.effective_style;
+    let ex_style = dialog.effective_ex_style;
+    if style & WS_DISABLED != 0 {
+        widget.set("enabled", UiValue::Bool(false));
+    }
+    if ex_style & WS_EX_LAYOUTRTL != 0 {
+        widget.set("layoutDirection", UiValue::Enum("Qt::RightToLeft".into()));
+    }
+    if style & WS_THICKFRAME != 0 {
+        widget.set("sizeGripEnabled", UiValue::Bool(true));
+    }
+    if style & (DS_MODALFRAME | DS_SYSMODAL) != 0 {
+        widget.set("modal", UiValue::Bool(true));
+    }
+
+    // Child dialogs, such as property pages, are embedded in other windows.
+    if style & WS_CHILD != 0 {
+        return;
+    }
+    let caption = style & WS_CAPTION == WS_CAPTION;
+    let system_menu = caption && style & WS_SYSMENU != 0;
+    let mut hints = Vec::new();
+    if caption {
+        hints.push("Qt::WindowTitleHint");
+    }
+    if system_menu {
+        hints.push("Qt::WindowSystemMenuHint");
+        hints.push("Qt::WindowCloseButtonHint");
+        if style & WS_MINIMIZEBOX != 0 {
+            hints.push("Qt::WindowMinimizeButtonHint");
+        }
+        if style & WS_MAXIMIZEBOX != 0 {
+            hints.push("Qt::WindowMaximizeButtonHint");
+        }
+    }
+    if (style & DS_CONTEXTHELP != 0 || ex_style & WS_EX_CONTEXTHELP != 0) && system_menu {
+        hints.push("Qt::WindowContextHelpButtonHint");
+    }
+    if ex_style & WS_EX_TOPMOST != 0 {
+        hints.push("Qt::WindowStaysOnTopHint");
+    }
+    let tool = ex_style & WS_EX_TOOLWINDOW != 0;
+    if tool || hints != ["Qt::WindowTitleHint", "Qt::WindowSystemMenuHint", "Qt::WindowCloseButtonHint"] {
+        let mut flags = vec![if tool { "Qt::Tool" } else { "Qt::Dialog" }, "Qt::CustomizeWindowHint"];
+        if !caption && style & (WS_BORDER | WS_DLGFRAME | WS_THICKFRAME) == 0 {
+            flags.push("Qt::FramelessWindowHint");
+        }
+        flags.extend(hints);
+        widget.set("windowFlags", UiValue::Set(flags.join("|")));
+    }
+}
+
+// Warns about controls of a dialog sharing a mnemonic, of which Windows
+// only ever reaches the first.
+fn check_mnemonics(dialog: &RcDialogEx, diagnostics: &mut Diagnostics) {
+    let mut seen: Vec<(char, &DialogControl)> = Vec::new();
+    for control in &dialog.controls {
+        let prefixed = control.class.eq_ignore_ascii_case("Button")
+            || (control.class.eq_ignore_ascii_case("Static") && control.effective_style & SS_NOPREFIX == 0);
+        let Some(key) = mnemonic(&control.text).filter(|_| prefixed) else {
+            continue;
+        };
+        let key = key.to_lowercase().next().unwrap_or(key);
+        match seen.iter().find(|(k, _)| *k == key) {
+            Some((_, first)) => diagnostics.warning(
+                Some(control.span),
+                format!(
+                    "mnemonic '{}' of \"{}\" is already used by \"{}\"",
+                    key, control.text, first.text
+                ),
+            ),
+            None => seen.push((key, control)),
+        }
+    }
+}
+
+// A rectangle in pixels: x, y, width and height.
+pub(crate) type Rect = (i32, i32, i32, i32);
+
+pub(crate) fn widget_rect(widget: &UiWidget) -> Rect {
+    match widget.get("geometry") {
+        Some(&UiValue::Rect { x, y, width, height }) => (x, y, width, height),
+        _ => (0, 0, 0, 0),
+    }
+}
+
+fn contains(outer: Rect, inner: Rect) -> bool {
+    inner.0 >= outer.0 && inner.1 >= outer.1 && inner.0 + inner.2 <= outer.0 + outer.2 && inner.1 + inner.3 <= outer.1 + outer.3
+}
+
+fn overlaps(a: Rect, b: Rect) -> bool {
+    a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+}
+
+// Group boxes are drawn behind their sibling controls in Windows, while in
+// Qt the controls belong inside the QGroupBox. Every widget enclosed by a
+// group box becomes a child of the smallest one enclosing it, at
+// coordinates relative to it. Widgets crossing a group box border are
+// reported, as Qt clips them to the group box they end up in.
+fn nest_in_group_boxes(children: Vec<(UiWidget, &DialogControl)>, diagnostics: &mut Diagnostics) -> Vec<UiWidget> {
+    let rects: Vec<Rect> = children.iter().map(|(widget, _)| widget_rect(widget)).collect();
+    let area = |i: usize| rects[i].2 * rects[i].3;
+    let boxes: Vec<usize> = (0..children.len()).filter(|&i| children[i].0.class == "QGroupBox").collect();
+
+    let mut parents = vec![None; children.len()];
+    for (i, (widget, control)) in children.iter().enumerate() {
+        // A group box of the same size as another goes into the earlier one.
+        let enclosing: Vec<usize> = boxes
+            .iter()
+            .copied()
+            .filter(|&b| b != i && contains(rects[b], rects[i]) && (area(b) > area(i) || b < i))
+            .collect();
+        // Crossing group boxes are reported once, at the later one.
+        for &b in boxes.iter().filter(|&&b| b < i || !boxes.contains(&i)) {
+            if overlaps(rects[b], rects[i]) && !contains(rects[b], rects[i]) && !contains(rects[i], rects[b]) {
+                diagnostics.warning(
+                    Some(control.span),
+                    format!("'{}' crosses the border of group box '{}'", widget.name, children[b].0.name),
+                );
+            }
+        }
+        let Some(&parent) = enclosing.iter().min_by_key(|&&b| area(b)) else {
+            continue;
+        };
+        if let Some(&other) = enclosing.iter().find(|&&b| !contains(rects[b], rects[parent])) {
+            diagnostics.warning(
+                Some(control.span),
+                format!(
+                    "'{}' is inside overlapping group boxes '{}' and '{}'; it is placed in '{}'",
+                    widget.name, children[parent].0.name, children[other].0.name, children[parent].0.name
+                ),
+            );
+        }
+        parents[i] = Some(parent);
+    }
+
+    let mut widgets: Vec<Option<UiWidget>> = children.into_iter().map(|(widget, _)| Some(widget)).collect();
+    nest_children(None, &parents, &rects, &mut widgets)
+}
+
+// Takes the widgets whose parent is `parent` out of `widgets`, with their
+// own children nested and their geometry relative to `parent`.
+fn nest_children(parent: Option<usize>, parents: &[Option<usize>], rects: &[Rect], widgets: &mut [Option<UiWidget>]) -> Vec<UiWidget> {
+    let mut nested = Vec::new();
+    for i in 0..widgets.len() {
+        if parents[i] != parent {
+            continue;
+        }
+        let Some(mut widget) = widgets[i].take() else {
+            continue;
+        };
+        if let Some(p) = parent {
+            let (x, y, width, height) = rects[i];
+            widget.set(
+                "geometry",
+                UiValue::Rect {
+                    x: x - rects[p].0,
+                    y: y - rects[p].1,
+                    width,
+                    height,
+                },
+            );
+        }
+        widget.children.extend(nest_children(Some(i), parents, rects, widgets));
+        nested.push(widget);
+    }
+    nested
+}
+
+fn is_auto_radio_button(control: &DialogControl) -> bool {
+    control.class.eq_ignore_ascii_case("Button") && control.effective_style & BS_TYPEMASK == BS_AUTORADIOBUTTON
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::ui::{UiLayout, UiLayoutContent};
+    use crate::{parse_str, PreprocessOptions};
+
+    /// Converts the first dialog of `source`. Dialogs without a FONT use the
+    /// system font, where a dialog unit is two pixels.
+    pub(crate) fn convert(source: &str, options: &FormOptions) -> (UiForm, Diagnostics) {
+        let mut diagnostics = Diagnostics::new();
+        let script = parse_str(source, &PreprocessOptions::default(), &mut diagnostics).unwrap();
+        let (resource, dialog) = script.dialogs().next().expect("a dialog");
+        let class_name = options.naming.class_name(&resource.id);
+        let init = script.dialog_init(&resource.id);
+        let form = dialog_form(dialog, init, &class_name, &script, options, &mut diagnostics);
+        (form, diagnostics)
+    }
+
+    /// The form of a dialog with `controls`, converted with the default options.
+    pub(crate) fn form(controls: &str) -> UiForm {
+        convert(&format!("IDD_TEST DIALOGEX 0, 0, 200, 100\nBEGIN\n{}\nEND\n", controls), &FormOptions::default()).0
+    }
+
+    /// The widget named `name`, at any depth.
+    pub(crate) fn find<'a>(widget: &'a UiWidget, name: &str) -> Option<&'a UiWidget> {
+        if widget.name == name {
+            return Some(widget);
+        }
+        let in_layout = widget.layout.iter().flat_map(layout_widgets);
+        widget.children.iter().chain(in_layout).find_map(|child| find(child, name))
+    }
+
+    fn layout_widgets(layout: &UiLayout) -> Vec<&UiWidget> {
+        layout
+            .items
+            .iter()
+            .flat_map(|item| match &item.content {
+                UiLayoutContent::Widget(widget) => vec![widget],
+                UiLayoutContent::Layout(inner) => layout_widgets(inner),
+                UiLayoutContent::Spacer(_) => Vec::new(),
+            })
+            .collect()
+    }
+
+    pub(crate) fn widget<'a>(form: &'a UiForm, name: &str) -> &'a UiWidget {
+        find(&form.widget, name).unwrap_or_else(|| panic!("no widget {} in {:#?}", name, form.widget))
+    }
+
+    #[test]
+    fn class_and_file_names() {
+        let id = |name: &str| RcId {
+            name: name.into(),
+            value: Some(100),
+        };
+        assert_eq!(UiNaming::CamelCase.class_name(&id("IDD_ABOUTBOX")), "AboutBox");
+        assert_eq!(UiNaming::CamelCase.class_name(&id("IDD_USER_SETTINGS_DLG")), "UserSettingsDlg");
+        assert_eq!(UiNaming::CamelCase.class_name(&id("100")), "Dialog100");
+        assert_eq!(UiNaming::Id.class_name(&id("IDD_ABOUTBOX")), "IDD_ABOUTBOX");
+        assert_eq!(UiNaming::Lowercase.file_name("AboutBox"), "aboutbox.ui");
+        assert_eq!(UiNaming::CamelCase.file_name("AboutBox"), "AboutBox.ui");
+        assert!("snake".parse::<UiNaming>().is_err());
+    }
+
+    #[test]
+    fn dialog_becomes_a_named_qdialog() {
+        let (form, _) = convert(
+            "IDD_ABOUTBOX DIALOGEX 0, 0, 150, 60\nCAPTION \"About\"\nBEGIN\n\
+             LTEXT \"Version\", IDC_STATIC, 5, 5, 60, 8\n\
+             LTEXT \"Copyright\", -1, 5, 20, 60, 8\n\
+             EDITTEXT IDC_USER_NAME, 70, 5, 60, 12\n\
+             PUSHBUTTON \"Go\", IDC_DELETE, 70, 20, 60, 14\n\
+             END\n",
+            &FormOptions::default(),
+        );
+        assert_eq!((form.class.as_str(), form.widget.class.as_str()), ("AboutBox", "QDialog"));
+        assert_eq!(form.widget.name, "AboutBox");
+        assert_eq!(form.widget.get("windowTitle"), Some(&UiValue::String("About".into())));
+        let names: Vec<&str> = form.widget.children.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, ["label", "label_2", "userName", "deletePushButton"]);
+    }
+
+    #[test]
+    fn geometry_is_converted_to_pixels() {
+        let form = form("EDITTEXT IDC_NAME, 10, 20, 30, 12");
+        let edit = widget(&form, "name");
+        assert_eq!(edit.class, "QLineEdit");
+        assert_eq!(
+            edit.get("geometry"),
+            Some(&UiValue::Rect {
+                x: 20,
+                y: 40,
+                width: 60,
+                height: 24
+            })
+        );
+    }
+
+    #[test]
+    fn dialog_font_sets_the_scale() {
+        let (form, _) = convert(
+            "1 DIALOGEX 0, 0, 100, 80\nFONT 8, \"MS Shell Dlg\"\nBEGIN\n  PUSHBUTTON \"Go\", 10, 4, 8, 50, 14\nEND\n",
+            &FormOptions::default(),
+        );
+        let size = |widget: &UiWidget| widget.get("geometry").cloned();
+        assert_eq!(
+            size(&form.widget),
+            Some(UiValue::Rect {
+                x: 0,
+                y: 0,
+                width: 150,
+                height: 130
+            })
+        );
+        assert_eq!(
+            size(&form.widget.children[0]),
+            Some(UiValue::Rect {
+                x: 6,
+                y: 13,
+                width: 75,
+                height: 23
+            })
+        );
+    }
+
+    fn button_group(form: &UiForm, name: &str) -> Option<String> {
+        let attribute = widget(form, name).attributes.iter().find(|a| a.name == "buttonGroup");
+        attribute.map(|a| match &a.value {
+            UiValue::String(group) => group.clone(),
+            other => panic!("buttonGroup is {:?}", other),
+        })
+    }
+
+    #[test]
+    fn radio_groups_become_button_groups() {
+        let form = form(
+            "AUTORADIOBUTTON \"Small\", IDC_SMALL, 5, 5, 60, 10, WS_GROUP\n\
+             AUTORADIOBUTTON \"Large\", IDC_LARGE, 5, 20, 60, 10\n\
+             AUTORADIOBUTTON \"Red\", IDC_RED, 80, 5, 60, 10, WS_GROUP\n\
+             AUTORADIOBUTTON \"Blue\", IDC_BLUE, 80, 20, 60, 10\n\
+             RADIOBUTTON \"Manual\", IDC_MANUAL, 5, 40, 60, 10, WS_GROUP\n\
+             AUTORADIOBUTTON \"Alone\", IDC_ALONE, 80, 40, 60, 10, WS_GROUP",
+        );
+        assert_eq!(form.button_groups, ["smallGroup", "redGroup", "aloneGroup"]);
+        assert_eq!(button_group(&form, "small").as_deref(), Some("smallGroup"));
+        assert_eq!(button_group(&form, "large").as_deref(), Some("smallGroup"));
+        assert_eq!(button_group(&form, "red").as_deref(), Some("redGroup"));
+        assert_eq!(button_group(&form, "blue").as_deref(), Some("redGroup"));
+        assert_eq!(button_group(&form, "manual"), None);
+        assert_eq!(button_group(&form, "alone").as_deref(), Some("aloneGroup"));
+    }
+
+    #[test]
+    fn other_controls_do_not_split_a_radio_group() {
+        let form = form(
+            "AUTORADIOBUTTON \"Small\", IDC_SMALL, 5, 5, 60, 10, WS_GROUP\n\
+             EDITTEXT IDC_WIDTH, 70, 5, 40, 12\n\
+             AUTORADIOBUTTON \"Large\", IDC_LARGE, 5, 20, 60, 10",
+        );
+        assert_eq!(form.button_groups, ["smallGroup"]);
+        assert_eq!(button_group(&form, "large").as_deref(), Some("smallGroup"));
+    }
+
+    #[test]
+    fn controls_move_into_their_group_box() {
+        let (form, diagnostics) = convert(
+            "IDD_TEST DIALOGEX 0, 0, 200, 100\nBEGIN\n\
+             GROUPBOX \"Options\", IDC_OPTIONS, 5, 5, 100, 60\n\
+             GROUPBOX \"Inner\", IDC_INNER, 10, 30, 80, 30\n\
+             AUTOCHECKBOX \"Fast\", IDC_FAST, 10, 15, 60, 10\n\
+             AUTOCHECKBOX \"Deep\", IDC_DEEP, 15, 40, 60, 10\n\
+             EDITTEXT IDC_NAME, 120, 5, 60, 12\n\
+             END\n",
+            &FormOptions::default(),
+        );
+        assert!(!diagnostics.render().contains("group box"), "{}", diagnostics.render());
+        let names = |widget: &UiWidget| widget.children.iter().map(|w| w.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&form.widget), ["options", "name"]);
+        let options = widget(&form, "options");
+        assert_eq!(names(options), ["inner", "fast"]);
+        assert_eq!(names(widget(&form, "inner")), ["deep"]);
+        assert_eq!(
+            widget(&form, "fast").get("geometry"),
+            Some(&UiValue::Rect {
+                x: 10,
+                y: 20,
+                width: 120,
+                height: 20
+            })
+        );
+        assert_eq!(
+            widget(&form, "deep").get("geometry"),
+            Some(&UiValue::Rect {
+                x: 10,
+                y: 20,
+                width: 120,
+                height: 20
+            })
+        );
+    }
+
+    #[test]
+    fn controls_crossing_a_group_box_are_reported() {
+        let (form, diagnostics) = convert(
+            "IDD_TEST DIALOGEX 0, 0, 200, 100\nBEGIN\n\
+             GROUPBOX \"Options\", IDC_OPTIONS, 5, 5, 100, 60\n\
+             EDITTEXT IDC_NAME, 80, 20, 60, 12\n\
+             END\n",
+            &FormOptions::default(),
+        );
+        assert_eq!(form.widget.children.len(), 2);
+        assert!(diagnostics
+            .render()
+            .contains("'name' crosses the border of group box 'options'"));
+    }
+
+    // A dialog with the standard button IDs of winuser.h defined.
+    fn standard_buttons(controls: &str) -> UiForm {
+        let script = format!(
+            "#define IDOK 1\n#define IDCANCEL 2\n#define IDYES 6\n#define IDNO 7\n\
+             IDD_TEST DIALOGEX 0, 0, 200, 100\nBEGIN\n{}\nEND\n",
+            controls
+        );
+        convert(&script, &FormOptions::default()).0
+    }
+
+    #[test]
+    fn standard_buttons_become_a_button_box() {
+        let form = standard_buttons(
+            "EDITTEXT IDC_NAME, 5, 5, 100, 12\n\
+             DEFPUSHBUTTON \"OK\", IDOK, 90, 80, 50, 14\n\
+             PUSHBUTTON \"Cancel\", IDCANCEL, 145, 80, 50, 14",
+        );
+        let names: Vec<&str> = form.widget.children.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, ["name", "buttonBox"]);
+        let button_box = widget(&form, "buttonBox");
+        assert_eq!(button_box.class, "QDialogButtonBox");
+        assert_eq!(
+            button_box.get("geometry"),
+            Some(&UiValue::Rect {
+                x: 180,
+                y: 160,
+                width: 210,
+                height: 28
+            })
+        );
+        assert_eq!(button_box.get("orientation"), Some(&UiValue::Enum("Qt::Horizontal".into())));
+        assert_eq!(
+            button_box.get("standardButtons"),
+            Some(&UiValue::Set("QDialogButtonBox::Ok|QDialogButtonBox::Cancel".into()))
+        );
+        assert_eq!(button_box.comment, None);
+        let connections: Vec<_> = form
+            .connections
+            .iter()
+            .map(|c| (c.sender.as_str(), c.signal.as_str(), c.receiver.as_str(), c.slot.as_str()))
+            .collect();
+        assert_eq!(
+            connections,
+            [
+                ("buttonBox", "accepted()", "Test", "accept()"),
+                ("buttonBox", "rejected()", "Test", "reject()")
+            ]
+        );
+    }
+
+    #[test]
+    fn button_box_notes_relabelled_buttons() {
+        let form = standard_buttons(
+            "PUSHBUTTON \"&Save\", IDYES, 145, 5, 50, 14\n\
+             PUSHBUTTON \"&No\", IDNO, 145, 22, 50, 14",
+        );
+        let button_box = widget(&form, "buttonBox");
+        assert_eq!(button_box.get("orientation"), Some(&UiValue::Enum("Qt::Vertical".into())));
+        assert_eq!(
+            button_box.comment.as_deref(),
+            Some("TODO: relabel the buttons: Yes was \"&Save\"")
+        );
+    }
+
+    #[test]
+    fn scattered_standard_buttons_stay_push_buttons() {
+        for controls in [
+            "DEFPUSHBUTTON \"OK\", IDOK, 5, 5, 50, 14\nPUSHBUTTON \"Cancel\", IDCANCEL, 145, 80, 50, 14",
+            "DEFPUSHBUTTON \"OK\", IDOK, 5, 80, 50, 14\n\
+             PUSHBUTTON \"More\", IDC_MORE, 60, 80, 50, 14\n\
+             PUSHBUTTON \"Cancel\", IDCANCEL, 145, 80, 50, 14",
+        ] {
+            let form = standard_buttons(controls);
+            assert!(form.connections.is_empty());
+            assert!(form.widget.children.iter().all(|w| w.class == "QPushButton"), "{:#?}", form.widget);
+        }
+    }
+
+    #[test]
+    fn tab_stops_give_the_tab_order() {
+        let form = form(
+            "LTEXT \"Name\", IDC_STATIC, 5, 5, 40, 8\n\
+             EDITTEXT IDC_NAME, 50, 5, 100, 12\n\
+             EDITTEXT IDC_NOTE, 50, 20, 100, 12, NOT WS_TABSTOP\n\
+             AUTORADIOBUTTON \"Small\", IDC_SMALL, 5, 40, 60, 10, WS_GROUP | WS_TABSTOP\n\
+             AUTORADIOBUTTON \"Large\", IDC_LARGE, 5, 55, 60, 10, NOT WS_TABSTOP\n\
+             PUSHBUTTON \"Run\", IDC_RUN, 100, 80, 40, 14",
+        );
+        assert_eq!(form.tab_stops, ["name", "small", "run"]);
+        let no_focus = Some(&UiValue::Enum("Qt::NoFocus".into()));
+        let focus = |name: &str| widget(&form, name).get("focusPolicy");
+        assert_eq!(focus("label"), no_focus);
+        assert_eq!(focus("note"), no_focus);
+        assert_eq!(focus("name"), None);
+        assert_eq!(focus("small"), None);
+        assert_eq!(focus("large"), None);
+    }
+
+    #[test]
+    fn labels_with_a_mnemonic_get_a_buddy() {
+        let (form, diagnostics) = convert(
+            "IDD_TEST DIALOGEX 0, 0, 200, 100\nBEGIN\n\
+             LTEXT \"&Name:\", IDC_STATIC, 5, 5, 40, 8\n\
+             LTEXT \"(required)\", IDC_STATIC, 5, 15, 40, 8\n\
+             EDITTEXT IDC_NAME, 50, 5, 100, 12\n\
+             LTEXT \"&Plain\", IDC_STATIC, 5, 30, 40, 8, SS_NOPREFIX\n\
+             EDITTEXT IDC_PLAIN, 50, 30, 100, 12\n\
+             LTEXT \"&Last\", IDC_STATIC, 5, 50, 40, 8\n\
+             END\n",
+            &FormOptions::default(),
+        );
+        assert_eq!(widget(&form, "label").get("buddy"), Some(&UiValue::CString("name".into())));
+        assert_eq!(widget(&form, "label").get("text"), Some(&UiValue::String("&Name:".into())));
+        assert_eq!(widget(&form, "label_2").get("buddy"), None);
+        assert_eq!(widget(&form, "label_3").get("buddy"), None);
+        assert_eq!(widget(&form, "label_4").get("buddy"), None);
+        assert!(!diagnostics.render().contains("mnemonic"), "{}", diagnostics.render());
+    }
+
+    #[test]
+    fn shared_mnemonics_are_reported() {
+        let (_, diagnostics) = convert(
+            "IDD_TEST DIALOGEX 0, 0, 200, 100\nBEGIN\n\
+             LTEXT \"&Name:\", IDC_STATIC, 5, 5, 40, 8\n\
+             EDITTEXT IDC_NAME, 50, 5, 100, 12\n\
+             AUTOCHECKBOX \"&notify\", IDC_NOTIFY, 5, 20, 60, 10\n\
+             LTEXT \"&Other\", IDC_STATIC, 5, 35, 40, 8, SS_NOPREFIX\n\
+             PUSHBUTTON \"&Open\", IDC_OPEN, 100, 80, 40, 14\n\
+             END\n",
+            &FormOptions::default(),
+        );
+        let rendered = diagnostics.render();
+        assert!(rendered.contains("mnemonic 'n' of \"&notify\" is already used by \"&Name:\""), "{}", rendered);
+        assert_eq!(rendered.matches("mnemonic").count(), 1, "{}", rendered);
+    }
+
+    fn styled_dialog(statements: &str) -> UiWidget {
+        let script = format!("IDD_TEST DIALOGEX 0, 0, 200, 100\n{}\nBEGIN\nEND\n", statements);
+        convert(&script, &FormOptions::default()).0.widget
+    }
+
+    #[test]
+    fn dialog_styles_set_window_flags() {
+        let flags = |statements: &str| styled_dialog(statements).get("windowFlags").cloned();
+        let set = |flags: &str| Some(UiValue::Set(flags.into()));
+        assert_eq!(flags("STYLE DS_MODALFRAME | WS_POPUP | WS_CAPTION | WS_SYSMENU"), None);
+        assert_eq!(flags("STYLE WS_CHILD"), None);
+        assert_eq!(flags("STYLE WS_POPUP"), set("Qt::Dialog|Qt::CustomizeWindowHint|Qt::FramelessWindowHint"));
+        assert_eq!(
+            flags("STYLE WS_POPUP | WS_CAPTION | WS_SYSMENU | WS_MINIMIZEBOX\nEXSTYLE WS_EX_TOPMOST"),
+            set(
+                "Qt::Dialog|Qt::CustomizeWindowHint|Qt::WindowTitleHint|Qt::WindowSystemMenuHint|\
+                 Qt::WindowCloseButtonHint|Qt::WindowMinimizeButtonHint|Qt::WindowStaysOnTopHint"
+            )
+        );
+        assert_eq!(
+            flags("STYLE WS_POPUP | WS_CAPTION | WS_SYSMENU\nEXSTYLE WS_EX_TOOLWINDOW"),
+            set("Qt::Tool|Qt::CustomizeWindowHint|Qt::WindowTitleHint|Qt::WindowSystemMenuHint|Qt::WindowCloseButtonHint")
+        );
+    }
+
+    #[test]
+    fn dialog_styles_set_properties() {
+        let dialog = styled_dialog(
+            "STYLE DS_MODALFRAME | WS_POPUP | WS_CAPTION | WS_SYSMENU | WS_THICKFRAME | WS_DISABLED\n\
+             EXSTYLE WS_EX_LAYOUTRTL",
+        );
+        assert_eq!(dialog.get("modal"), Some(&UiValue::Bool(true)));
+        assert_eq!(dialog.get("sizeGripEnabled"), Some(&UiValue::Bool(true)));
+        assert_eq!(dialog.get("enabled"), Some(&UiValue::Bool(false)));
+        assert_eq!(dialog.get("layoutDirection"), Some(&UiValue::Enum("Qt::RightToLeft".into())));
+
+        let plain = styled_dialog("STYLE WS_POPUP | WS_CAPTION | WS_SYSMENU");
+        for property in ["modal", "sizeGripEnabled", "enabled", "layoutDirection"] {
+            assert_eq!(plain.get(property), None, "{}", property);
+        }
+    }
+}
@@ -1,119 +1,373 @@
 extern crate xml;
 
-use regex::Regex;
-use std::collections::HashMap;
+use crate::diagnostics::Diagnostics;
+use crate::error::Result;
+use crate::form::{dialog_form, FormOptions};
+use crate::resource::{RcResourceData, RcStringTable, ResourceScript};
+use crate::ui::{write_form, UiForm};
+use log::info;
+use std::collections::HashSet;
 use std::fs::File;
-use xml::writer::{EmitterConfig, XmlEvent}; // Import the Regex module
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use xml::writer::{EmitterConfig, XmlEvent};
 
-pub fn create_qrc_file(resources: &HashMap<String, String>, output_path: &str) {
-    let file = File::create(output_path).unwrap();
-    let mut writer = EmitterConfig::new()
-        .perform_indent(true)
-        .create_writer(file);
+pub fn output_bitmaps<W: Write>(script: &ResourceScript, out: &mut W) -> Result<()> {
+    writeln!(out, "void qtMfcInitBitmapResources() {{")?;
+    writeln!(out, "    qtMfcBitmapResources.clear();")?;
+    for (resource, bitmap) in script.bitmaps() {
+        writeln!(out, "    // {} BITMAP {}", resource.id, bitmap.file)?;
+        writeln!(
+            out,
+            "    qtMfcBitmapResources.insert({}, new CBitmap({}));",
+            resource.id.to_cpp(), bitmap.file
+        )?;
+        if let Some(width) = bitmap.width {
+            writeln!(out, "    // Width: {}", width)?;
+        }
+        if let Some(height) = bitmap.height {
+            writeln!(out, "    // Height: {}", height)?;
+        }
+        if let Some(color_depth) = bitmap.color_depth {
+            writeln!(out, "    // Color Depth: {}", color_depth)?;
+        }
+        if let Some(compression) = &bitmap.compression {
+            writeln!(out, "    // Compression: {}", compression)?;
+        }
+        if let Some(palette) = &bitmap.palette {
+            writeln!(out, "    // Palette: {}", palette)?;
+        }
+        if let Some(dpi) = bitmap.dpi {
+            writeln!(out, "    // DPI: {}", dpi)?;
+        }
+        if let Some(color_mode) = &bitmap.color_mode {
+            writeln!(out, "    // Color Mode: {}", color_mode)?;
+        }
+        if let Some(compression_level) = bitmap.compression_level {
+            writeln!(out, "    // Compression Level: {}", compression_level)?;
+        }
+        if let Some(author) = &bitmap.author {
+            writeln!(out, "    // Author: {}", author)?;
+        }
+    }
+    writeln!(out, "}}")?;
+    Ok(())
+}
 
-    writer.write(XmlEvent::start_element("RCC")).unwrap();
-    writer
-        .write(XmlEvent::start_element("qresource").attr("prefix", "/"))
-        .unwrap();
+pub fn output_icons<W: Write>(script: &ResourceScript, out: &mut W) -> Result<()> {
+    writeln!(out, "void qtInitIconResources() {{")?;
+    writeln!(out, "    qtIconNames.clear();")?;
+    writeln!(out, "    qtIconResources.clear();")?;
+    writeln!(out, "    // Icon with lowest ID value placed first to ensure application icon remains consistent on all systems.")?;
+    for (resource, icon) in script.icons() {
+        writeln!(out, "    // {} ICON {}", resource.id, icon.file)?;
+        writeln!(out, "    qtIconNames.insert({}, {});", resource.id.to_cpp(), icon.file)?;
+    }
+    writeln!(out, "}}")?;
+    Ok(())
+}
 
-    for (_name, path) in resources {
-        writer.write(XmlEvent::start_element("file")).unwrap();
-        writer.write(XmlEvent::characters(path)).unwrap();
-        writer.write(XmlEvent::end_element()).unwrap(); // file
+fn output_string_table<W: Write>(string_table: &RcStringTable, out: &mut W) -> Result<()> {
+    for item in &string_table.table {
+        writeln!(
+            out,
+            "    qtMfcStringResources.insert({}, \"{}\");",
+            item.id.to_cpp(),
+            item.text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+        )?;
+    }
+    Ok(())
+}
+
+pub fn output_string_tables<W: Write>(script: &ResourceScript, out: &mut W) -> Result<()> {
+    writeln!(out, "void qtMfcInitStringResources() {{")?;
+    writeln!(out, "    qtMfcStringResources.clear();")?;
+    for (resource, string_table) in script.string_tables() {
+        match &resource.language {
+            Some(language) => writeln!(out, "    // STRINGTABLE LANGUAGE {}", language)?,
+            None => writeln!(out, "    // STRINGTABLE")?,
+        }
+        writeln!(out, "    // BEGIN")?;
+        output_string_table(string_table, out)?;
+        writeln!(out, "    // END")?;
+    }
+    writeln!(
+        out,
+        "    // AFX resources
+        qtMfcStringResources.insert(AFX_IDS_ALLFILTER, \"All files|\");
+        qtMfcStringResources.insert(AFX_IDS_OPENFILE, \"Open\");
+        qtMfcStringResources.insert(AFX_IDS_SAVEFILE, \"Save As\");
+        qtMfcStringResources.insert(AFX_IDS_SAVEFILECOPY, \"Save As\");
+        qtMfcStringResources.insert(AFX_IDS_UNTITLED, \"Untitled\");
+        qtMfcStringResources.insert(AFX_IDP_ASK_TO_SAVE, \"Save changes to %s?\");
+        qtMfcStringResources.insert(AFX_IDP_FAILED_TO_CREATE_DOC, \"Failed to create empty document.\");
+    ")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+pub fn output_toolbars<W: Write>(script: &ResourceScript, out: &mut W) -> Result<()> {
+    for (resource, toolbar) in script.toolbars() {
+        writeln!(
+            out,
+            "void qtMfcInitToolBarResource_{}(UINT dlgID, CToolBar* parent) {{",
+            resource.id
+        )?;
+        writeln!(out, "    // Toolbar logic here...")?;
+        if !toolbar.numbers.is_empty() {
+            writeln!(out, "    // Button size: {:?}", toolbar.numbers)?;
+        }
+        for entry in &toolbar.table {
+            writeln!(out, "    // {}", entry)?;
+        }
+    }
+    writeln!(out, "void qtMfcInitToolBarResource(UINT dlgID, CToolBar* parent) {{")?;
+    writeln!(out, "    switch (dlgID) {{")?;
+    for (resource, _) in script.toolbars() {
+        writeln!(out, "        case {}:", resource.id.to_cpp())?;
+        writeln!(
+            out,
+            "            qtMfcInitToolBarResource_{}(dlgID, parent);",
+            resource.id
+        )?;
+        writeln!(out, "            break;")?;
+    }
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+pub fn output_accelerators<W: Write>(script: &ResourceScript, out: &mut W) -> Result<()> {
+    for (resource, accelerator_table) in script.accelerator_tables() {
+        writeln!(out, "// {} ACCELERATORS", resource.id)?;
+        writeln!(out, "ACCEL ACCEL_{}[] = {{", resource.id)?;
+        writeln!(out, "    // Accelerator table logic here...")?;
+        for entry in &accelerator_table.table {
+            writeln!(out, "    // {}", entry)?;
+        }
+        writeln!(out, "    {{ 0, 0, 0 }},")?;
+        writeln!(out, "}};")?;
+    }
+    writeln!(out, "ACCEL* qtMfcAcceleratorResource(UINT id) {{")?;
+    writeln!(out, "    switch (id) {{")?;
+    for (resource, _) in script.accelerator_tables() {
+        writeln!(out, "        case {}:", resource.id.to_cpp())?;
+        writeln!(out, "            return ACCEL_{};", resource.id)?;
+        writeln!(out, "            break;")?;
+    }
+    writeln!(out, "    }}")?;
+    writeln!(out, "    return NULL;")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+pub fn output_menus<W: Write>(script: &ResourceScript, out: &mut W) -> Result<()> {
+    for (resource, menu) in script.menus() {
+        writeln!(out, "void qtMfcInitMenuResource_{}(CMenu* parent) {{", resource.id)?;
+        writeln!(out, "    // Menu logic here...")?;
+        for entry in &menu.table {
+            writeln!(out, "    // {}", entry)?;
+        }
     }
+    writeln!(out, "void qtMfcInitMenuResource(UINT menuID, CMenu* parent) {{")?;
+    writeln!(out, "    switch (menuID) {{")?;
+    for (resource, _) in script.menus() {
+        writeln!(out, "        case {}:", resource.id.to_cpp())?;
+        writeln!(out, "            qtMfcInitMenuResource_{}(parent);", resource.id)?;
+        writeln!(out, "            break;")?;
+    }
+    writeln!(out, "    }}")?;
+    writeln!(out, "    // Fixup shortcuts")?;
+    writeln!(out, "    int menu = 0;")?;
+    writeln!(out, "    CMenu* subMenu = parent->GetSubMenu(menu);")?;
+    writeln!(out, "    while (subMenu) {{")?;
+    writeln!(out, "        foreach (QAction* action, subMenu->toQMenu()->actions()) {{")?;
+    writeln!(out, "            if (action->text().contains(\"\\t\")) {{")?;
+    writeln!(out, "                action->setShortcut(QKeySequence(action->text().split(\"\\t\").at(1)));")?;
+    writeln!(out, "            }}")?;
+    writeln!(out, "        }}")?;
+    writeln!(out, "        menu++;")?;
+    writeln!(out, "        subMenu = parent->GetSubMenu(menu);")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
 
-    writer.write(XmlEvent::end_element()).unwrap(); // qresource
-    writer.write(XmlEvent::end_element()).unwrap(); // RCC
+pub fn output_dialogs<W: Write>(script: &ResourceScript, out: &mut W) -> Result<()> {
+    for (resource, dialog) in script.dialogs() {
+        writeln!(
+            out,
+            "void qtMfcInitDialogResource_{}(CDialog* parent) {{",
+            resource.id
+        )?;
+        writeln!(out, "    // Dialog logic here...")?;
+        writeln!(
+            out,
+            "    // Rect: {}, {}, {}, {}",
+            dialog.x, dialog.y, dialog.width, dialog.height
+        )?;
+        if let Some(help_id) = dialog.help_id {
+            writeln!(out, "    // Help ID: {}", help_id)?;
+        }
+        if !dialog.caption.is_empty() {
+            writeln!(out, "    // Caption: {}", dialog.caption)?;
+        }
+        if let Some(style) = &dialog.style {
+            writeln!(out, "    // Style: {}", style)?;
+        }
+        if let Some(ex_style) = &dialog.ex_style {
+            writeln!(out, "    // ExStyle: {}", ex_style)?;
+        }
+        if let Some(font) = &dialog.font {
+            writeln!(
+                out,
+                "    // Font: {}pt \"{}\", weight {}{}, charset {}",
+                font.point_size,
+                font.face,
+                font.weight,
+                if font.italic { ", italic" } else { "" },
+                font.charset
+            )?;
+        }
+        if let Some(menu) = &dialog.menu {
+            writeln!(out, "    // Menu: {}", menu.to_cpp())?;
+        }
+        if let Some(class) = &dialog.class {
+            writeln!(out, "    // Class: {}", class)?;
+        }
+        for control in &dialog.controls {
+            writeln!(out, "    // {}", control)?;
+        }
+    }
+    writeln!(out, "void qtMfcInitDialogResource(UINT dlgID, CDialog* parent) {{")?;
+    writeln!(out, "    switch (dlgID) {{")?;
+    for (resource, _) in script.dialogs() {
+        writeln!(out, "        case {}:", resource.id.to_cpp())?;
+        writeln!(out, "            qtMfcInitDialogResource_{}(parent);", resource.id)?;
+        writeln!(out, "            break;")?;
+    }
+    writeln!(out, "        case 0:")?;
+    writeln!(out, "            // Allow blank dialogs.")?;
+    writeln!(out, "            break;")?;
+    writeln!(out, "        default:")?;
+    writeln!(out, "            qFatal(\"dialog resource not implemented...\");")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    Ok(())
 }
 
+/// Name of the resource collection written next to the forms.
+pub const QRC_FILE_NAME: &str = "resources.qrc";
+
+pub fn create_qrc_file<P: AsRef<Path>>(script: &ResourceScript, output_path: P) -> Result<()> {
+    write_qrc(script, File::create(output_path)?)
+}
 
-pub fn create_ui_file(dialog_name: &str, controls: &Vec<String>, output_path: &str) {
-    let file = File::create(output_path).unwrap();
+/// Lists every file-based resource of the script (bitmaps, icons, cursors...)
+/// in a Qt resource collection.
+pub fn write_qrc<W: Write>(script: &ResourceScript, out: W) -> Result<()> {
     let mut writer = EmitterConfig::new()
         .perform_indent(true)
-        .create_writer(file);
+        .create_writer(out);
 
-    writer
-        .write(XmlEvent::start_element("ui").attr("version", "4.0"))
-        .unwrap();
-    writer.write(XmlEvent::start_element("class")).unwrap();
-    writer.write(XmlEvent::characters(dialog_name)).unwrap();
-    writer.write(XmlEvent::end_element()).unwrap(); // class
-
-    writer
-        .write(
-            XmlEvent::start_element("widget")
-                .attr("class", "QWidget")
-                .attr("name", dialog_name),
+    writer.write(XmlEvent::start_element("RCC"))?;
+    writer.write(XmlEvent::start_element("qresource").attr("prefix", "/"))?;
+
+    let mut paths: Vec<String> = Vec::new();
+    for resource in script.resources() {
+        let file = match &resource.data {
+            RcResourceData::Bitmap(bitmap) => &bitmap.file,
+            RcResourceData::Icon(icon) => &icon.file,
+            RcResourceData::File(file) => file,
+            _ => continue,
+        };
+        let path = qrc_path(file);
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    for path in &paths {
+        writer.write(XmlEvent::start_element("file"))?;
+        writer.write(XmlEvent::characters(path))?;
+        writer.write(XmlEvent::end_element())?; // file
+    }
+
+    writer.write(XmlEvent::end_element())?; // qresource
+    writer.write(XmlEvent::end_element())?; // RCC
+    Ok(())
+}
+
+/// The path of a resource file inside the collection.
+pub fn qrc_path(file: &str) -> String {
+    file.replace('\\', "/")
+}
+
+/// Writes one .ui file per dialog of the script into `output_dir` and returns
+/// their paths. Dialogs that map to the same class name or file name, e.g.
+/// translations of one dialog, get a numeric suffix. File names are compared
+/// ignoring case, as on Windows.
+pub fn create_ui_files<P: AsRef<Path>>(
+    script: &ResourceScript,
+    output_dir: P,
+    options: &FormOptions,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<PathBuf>> {
+    let mut classes = HashSet::new();
+    let mut files = HashSet::new();
+    let mut paths = Vec::new();
+    for (resource, dialog) in script.dialogs() {
+        let base = options.naming.class_name(&resource.id);
+        let mut class_name = base.clone();
+        let mut n = 2;
+        while classes.contains(&class_name)
+            || files.contains(&options.naming.file_name(&class_name).to_lowercase())
+        {
+            class_name = format!("{}{}", base, n);
+            n += 1;
+        }
+        files.insert(options.naming.file_name(&class_name).to_lowercase());
+        classes.insert(class_name.clone());
+
+        let init = script.dialog_init(&resource.id);
+        let form = dialog_form(dialog, init, &class_name, script, options, diagnostics);
+        let path = output_dir.as_ref().join(options.naming.file_name(&class_name));
+        info!("Writing dialog {} to {}", resource.id, path.display());
+        write_ui(&form, File::create(&path)?)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+pub fn write_ui<W: Write>(form: &UiForm, out: W) -> Result<()> {
+    write_form(form, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::form::UiNaming;
+    use crate::{parse_str, PreprocessOptions};
+
+    #[test]
+    fn forms_never_share_a_file() {
+        let mut diagnostics = Diagnostics::new();
+        let script = parse_str(
+            "IDD_AB_C DIALOG 0, 0, 50, 50\nBEGIN\nEND\nIDD_ABC DIALOG 0, 0, 50, 50\nBEGIN\nEND\n",
+            &PreprocessOptions::default(),
+            &mut diagnostics,
         )
         .unwrap();
-    writer
-        .write(XmlEvent::start_element("property").attr("name", "geometry"))
-        .unwrap();
-    writer.write(XmlEvent::start_element("rect")).unwrap();
-    writer.write(XmlEvent::start_element("x")).unwrap();
-    writer.write(XmlEvent::characters("0")).unwrap();
-    writer.write(XmlEvent::end_element()).unwrap(); // x
-    writer.write(XmlEvent::start_element("y")).unwrap();
-    writer.write(XmlEvent::characters("0")).unwrap();
-    writer.write(XmlEvent::end_element()).unwrap(); // y
-    writer.write(XmlEvent::start_element("width")).unwrap();
-    writer.write(XmlEvent::characters("400")).unwrap();
-    writer.write(XmlEvent::end_element()).unwrap(); // width
-    writer.write(XmlEvent::start_element("height")).unwrap();
-    writer.write(XmlEvent::characters("300")).unwrap();
-    writer.write(XmlEvent::end_element()).unwrap(); // height
-    writer.write(XmlEvent::end_element()).unwrap(); // rect
-    writer.write(XmlEvent::end_element()).unwrap(); // property
-
-    let control_re =
-        Regex::new(r#"CONTROL\s+"(.+)",\s*(\d+),\s*BUTTON,\s*(\d+),\s*(\d+),\s*(\d+),\s*(\d+)"#)
-            .unwrap();
-    for control in controls {
-        if let Some(caps) = control_re.captures(control) {
-            let text = &caps[1];
-            let control_id = &caps[2];
-            let x = &caps[3];
-            let y = &caps[4];
-            let width = &caps[5];
-            let height = &caps[6];
-
-            writer
-                .write(
-                    XmlEvent::start_element("widget")
-                        .attr("class", "QPushButton")
-                        .attr("name", &format!("button_{}", control_id)),
-                )
-                .unwrap();
-            writer
-                .write(XmlEvent::start_element("property").attr("name", "geometry"))
-                .unwrap();
-            writer.write(XmlEvent::start_element("rect")).unwrap();
-            writer.write(XmlEvent::start_element("x")).unwrap();
-            writer.write(XmlEvent::characters(x)).unwrap();
-            writer.write(XmlEvent::end_element()).unwrap(); // x
-            writer.write(XmlEvent::start_element("y")).unwrap();
-            writer.write(XmlEvent::characters(y)).unwrap();
-            writer.write(XmlEvent::end_element()).unwrap(); // y
-            writer.write(XmlEvent::start_element("width")).unwrap();
-            writer.write(XmlEvent::characters(width)).unwrap();
-            writer.write(XmlEvent::end_element()).unwrap(); // width
-            writer.write(XmlEvent::start_element("height")).unwrap();
-            writer.write(XmlEvent::characters(height)).unwrap();
-            writer.write(XmlEvent::end_element()).unwrap(); // height
-            writer.write(XmlEvent::end_element()).unwrap(); // rect
-            writer.write(XmlEvent::end_element()).unwrap(); // property
-
-            writer
-                .write(XmlEvent::start_element("property").attr("name", "text"))
-                .unwrap();
-            writer.write(XmlEvent::characters(text)).unwrap();
-            writer.write(XmlEvent::end_element()).unwrap(); // property
-
-            writer.write(XmlEvent::end_element()).unwrap(); // widget
-        }
-    }
-
-    writer.write(XmlEvent::end_element()).unwrap(); // widget
-    writer.write(XmlEvent::end_element()).unwrap(); // ui
+        let dir = std::env::temp_dir().join(format!("rc2qt-ui-files-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let options = FormOptions {
+            naming: UiNaming::Lowercase,
+            ..FormOptions::default()
+        };
+        let paths = create_ui_files(&script, &dir, &options, &mut diagnostics).unwrap();
+        let files: Vec<_> = paths.iter().map(|p| p.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(files, ["abc.ui", "abc2.ui"]);
+        assert!(std::fs::read_to_string(&paths[1]).unwrap().contains("<class>Abc2</class>"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
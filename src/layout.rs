@@ -0,0 +1,333 @@
+use crate::form::{widget_rect, Rect, WidgetNames};
+use crate::ui::{UiLayout, UiLayoutContent, UiLayoutItem, UiSpacer, UiWidget};
+use log::info;
+
+// Pixels within which control edges count as aligned.
+pub(crate) const ALIGN_TOLERANCE: i32 = 6;
+
+// Pixels of free space in a button row that are kept as a spacer.
+const SPACER_GAP: i32 = 16;
+
+// Size hint of a vertical spacer, as Designer creates them.
+const VERTICAL_SPACER: (i32, i32) = (20, 40);
+
+const BUTTON_CLASSES: &[&str] = &["QPushButton", "QToolButton", "QCommandLinkButton", "QDialogButtonBox"];
+
+// Consecutive rows of a dialog that go into the same layout.
+enum Band {
+    // A label followed by its field on each row.
+    Form(Vec<Vec<usize>>),
+    // A single row of buttons.
+    Buttons(Vec<usize>),
+    Grid(Vec<usize>),
+}
+
+/// Replaces the geometry of the children of `widget`, and of the children of
+/// its group boxes, with layouts inferred from their positions. Children
+/// that cannot be placed on a grid, such as overlapping ones, keep their
+/// geometry.
+pub(crate) fn infer_layout(widget: &mut UiWidget, names: &mut WidgetNames) {
+    for child in &mut widget.children {
+        if child.class == "QGroupBox" {
+            infer_layout(child, names);
+        }
+    }
+    if widget.children.is_empty() {
+        return;
+    }
+
+    let rects: Vec<Rect> = widget.children.iter().map(widget_rect).collect();
+    let (rows, row_spans) = cells(&rects, |r| r.1, |r| r.1 + r.3);
+    if !fits_grid(&rows, &row_spans, &cells(&rects, |r| r.0, |r| r.0 + r.2)) {
+        info!("Keeping the geometry of the controls of {}, which overlap", widget.name);
+        return;
+    }
+
+    let bands = bands(&widget.children, &rects, &rows, &row_spans);
+    let ends_with_buttons = matches!(bands.last(), Some(Band::Buttons(_)));
+    let mut widgets: Vec<Option<UiWidget>> = widget.children.drain(..).map(Some).collect();
+    let layout = if let [band] = bands.as_slice() {
+        band_layout(band, &mut widgets, &rects, names)
+    } else {
+        let mut items = Vec::new();
+        for (i, band) in bands.iter().enumerate() {
+            // Keeps the buttons at the bottom when the dialog grows.
+            if i + 1 == bands.len() && ends_with_buttons {
+                items.push(spacer(false, VERTICAL_SPACER.0, VERTICAL_SPACER.1, names));
+            }
+            // A band of a single control, such as a group box, needs no
+            // layout of its own.
+            let content = match band {
+                Band::Grid(members) if members.len() == 1 => take(&mut widgets, members[0]),
+                _ => UiLayoutContent::Layout(band_layout(band, &mut widgets, &rects, names)),
+            };
+            items.push(UiLayoutItem { cell: None, content });
+        }
+        UiLayout {
+            class: "QVBoxLayout".into(),
+            name: names.unique("verticalLayout"),
+            items,
+        }
+    };
+    widget.layout = Some(layout);
+}
+
+// Clusters the start of each rectangle, as given by `start`, into rows or
+// columns. Returns the index of the row of each rectangle and how many rows
+// it spans up to its `end`.
+fn cells(rects: &[Rect], start: impl Fn(&Rect) -> i32, end: impl Fn(&Rect) -> i32) -> (Vec<usize>, Vec<usize>) {
+    let mut order: Vec<usize> = (0..rects.len()).collect();
+    order.sort_by_key(|&i| start(&rects[i]));
+
+    let mut starts: Vec<i32> = Vec::new();
+    let mut index = vec![0; rects.len()];
+    for i in order {
+        let value = start(&rects[i]);
+        match starts.last() {
+            Some(&first) if value - first <= ALIGN_TOLERANCE => {}
+            _ => starts.push(value),
+        }
+        index[i] = starts.len() - 1;
+    }
+    let spans = rects
+        .iter()
+        .zip(&index)
+        .map(|(rect, &i)| 1 + starts[i + 1..].iter().filter(|&&s| s < end(rect) - ALIGN_TOLERANCE).count())
+        .collect();
+    (index, spans)
+}
+
+// Whether no two rectangles share a cell.
+fn fits_grid(rows: &[usize], row_spans: &[usize], (columns, column_spans): &(Vec<usize>, Vec<usize>)) -> bool {
+    let mut taken = std::collections::HashSet::new();
+    for i in 0..rows.len() {
+        for row in rows[i]..rows[i] + row_spans[i] {
+            for column in columns[i]..columns[i] + column_spans[i] {
+                if !taken.insert((row, column)) {
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
+
+// Splits the rows into bands. Rows joined by a control spanning them stay
+// in the same band.
+fn bands(widgets: &[UiWidget], rects: &[Rect], rows: &[usize], row_spans: &[usize]) -> Vec<Band> {
+    let row_count = rows.iter().zip(row_spans).map(|(r, s)| r + s).max().unwrap_or(0);
+    let mut bands: Vec<Band> = Vec::new();
+    let mut start = 0;
+    while start < row_count {
+        let mut end = start + 1;
+        while let Some(e) = (0..rows.len())
+            .filter(|&i| rows[i] < end && rows[i] + row_spans[i] > end)
+            .map(|i| rows[i] + row_spans[i])
+            .max()
+        {
+            end = e;
+        }
+        let mut group: Vec<usize> = (0..rows.len()).filter(|&i| rows[i] >= start && rows[i] < end).collect();
+        group.sort_by_key(|&i| rects[i].0);
+        start = end;
+
+        let single_row = group.iter().all(|&i| row_spans[i] == 1);
+        let classes: Vec<&str> = group.iter().map(|&i| widgets[i].class.as_str()).collect();
+        if single_row && classes.iter().all(|c| BUTTON_CLASSES.contains(c)) {
+            bands.push(Band::Buttons(group));
+        } else if single_row && classes.len() == 2 && classes[0] == "QLabel" && classes[1] != "QLabel" {
+            match bands.last_mut() {
+                Some(Band::Form(rows)) => rows.push(group),
+                _ => bands.push(Band::Form(vec![group])),
+            }
+        } else {
+            match bands.last_mut() {
+                Some(Band::Grid(members)) => members.extend(group),
+                _ => bands.push(Band::Grid(group)),
+            }
+        }
+    }
+    bands
+}
+
+// The layout of a band, taking its widgets out of `widgets`.
+fn band_layout(band: &Band, widgets: &mut [Option<UiWidget>], rects: &[Rect], names: &mut WidgetNames) -> UiLayout {
+    match band {
+        Band::Form(rows) => UiLayout {
+            class: "QFormLayout".into(),
+            name: names.unique("formLayout"),
+            items: rows
+                .iter()
+                .enumerate()
+                .flat_map(|(row, pair)| {
+                    pair.iter().enumerate().map(move |(column, &i)| (row, column, i))
+                })
+                .map(|(row, column, i)| UiLayoutItem {
+                    cell: Some((row, column, 1, 1)),
+                    content: take(widgets, i),
+                })
+                .collect(),
+        },
+        Band::Buttons(buttons) => {
+            // Free space at the ends of the row, or between two buttons set
+            // apart, becomes a spacer.
+            let left = rects.iter().map(|r| r.0).min().unwrap_or(0);
+            let right = rects.iter().map(|r| r.0 + r.2).max().unwrap_or(0);
+            let mut items = Vec::new();
+            let mut edge = left;
+            for &i in buttons {
+                if rects[i].0 - edge > SPACER_GAP {
+                    items.push(spacer(true, rects[i].0 - edge, 20, names));
+                }
+                items.push(UiLayoutItem {
+                    cell: None,
+                    content: take(widgets, i),
+                });
+                edge = rects[i].0 + rects[i].2;
+            }
+            if right - edge > SPACER_GAP {
+                items.push(spacer(true, right - edge, 20, names));
+            }
+            UiLayout {
+                class: "QHBoxLayout".into(),
+                name: names.unique("horizontalLayout"),
+                items,
+            }
+        }
+        Band::Grid(members) => {
+            let band_rects: Vec<Rect> = members.iter().map(|&i| rects[i]).collect();
+            let (rows, row_spans) = cells(&band_rects, |r| r.1, |r| r.1 + r.3);
+            let (columns, column_spans) = cells(&band_rects, |r| r.0, |r| r.0 + r.2);
+            let mut order: Vec<usize> = (0..members.len()).collect();
+            order.sort_by_key(|&k| (rows[k], columns[k]));
+            UiLayout {
+                class: "QGridLayout".into(),
+                name: names.unique("gridLayout"),
+                items: order
+                    .into_iter()
+                    .map(|k| UiLayoutItem {
+                        cell: Some((rows[k], columns[k], row_spans[k], column_spans[k])),
+                        content: take(widgets, members[k]),
+                    })
+                    .collect(),
+            }
+        }
+    }
+}
+
+// A widget as a layout item, without its geometry.
+fn take(widgets: &mut [Option<UiWidget>], index: usize) -> UiLayoutContent {
+    let mut widget = widgets[index].take().expect("widget in two layouts");
+    widget.remove("geometry");
+    UiLayoutContent::Widget(widget)
+}
+
+fn spacer(horizontal: bool, width: i32, height: i32, names: &mut WidgetNames) -> UiLayoutItem {
+    let name = if horizontal { "horizontalSpacer" } else { "verticalSpacer" };
+    UiLayoutItem {
+        cell: None,
+        content: UiLayoutContent::Spacer(UiSpacer {
+            name: names.unique(name),
+            horizontal,
+            width,
+            height,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::form::tests::convert;
+    use crate::form::FormOptions;
+    use crate::ui::{UiForm, UiLayout, UiLayoutContent};
+
+    fn laid_out(controls: &str) -> UiForm {
+        let options = FormOptions {
+            layouts: true,
+            ..FormOptions::default()
+        };
+        convert(&format!("IDD_TEST DIALOGEX 0, 0, 200, 100\nBEGIN\n{}\nEND\n", controls), &options).0
+    }
+
+    // The layout in one line: items are separated by spaces, with the cell
+    // of grid and form items after an `@`.
+    fn outline(layout: &UiLayout) -> String {
+        let items: Vec<String> = layout
+            .items
+            .iter()
+            .map(|item| {
+                let content = match &item.content {
+                    UiLayoutContent::Widget(widget) => {
+                        assert_eq!(widget.get("geometry"), None);
+                        widget.name.clone()
+                    }
+                    UiLayoutContent::Layout(inner) => outline(inner),
+                    UiLayoutContent::Spacer(spacer) => spacer.name.clone(),
+                };
+                match item.cell {
+                    Some((row, column, 1, 1)) => format!("{}@{},{}", content, row, column),
+                    Some((row, column, rows, columns)) => format!("{}@{},{}+{}x{}", content, row, column, rows, columns),
+                    None => content,
+                }
+            })
+            .collect();
+        format!("{}({})", layout.class, items.join(" "))
+    }
+
+    #[test]
+    fn labelled_fields_and_buttons() {
+        let form = laid_out(
+            "LTEXT \"Name:\", IDC_STATIC, 5, 5, 40, 8\n\
+             EDITTEXT IDC_NAME, 50, 5, 100, 12\n\
+             LTEXT \"City:\", IDC_STATIC, 5, 20, 40, 8\n\
+             EDITTEXT IDC_CITY, 50, 20, 100, 12\n\
+             PUSHBUTTON \"Run\", IDC_RUN, 100, 80, 40, 14\n\
+             PUSHBUTTON \"Stop\", IDC_STOP, 145, 80, 40, 14",
+        );
+        assert!(form.widget.children.is_empty());
+        let layout = form.widget.layout.as_ref().unwrap();
+        assert_eq!(layout.name, "verticalLayout");
+        assert_eq!(
+            outline(layout),
+            "QVBoxLayout(QFormLayout(label@0,0 name@0,1 label_2@1,0 city@1,1) verticalSpacer \
+             QHBoxLayout(horizontalSpacer run stop))"
+        );
+    }
+
+    #[test]
+    fn other_controls_go_on_a_grid() {
+        let form = laid_out(
+            "AUTOCHECKBOX \"Fast\", IDC_FAST, 5, 5, 40, 10\n\
+             AUTOCHECKBOX \"Deep\", IDC_DEEP, 60, 5, 40, 10\n\
+             EDITTEXT IDC_PATH, 5, 20, 95, 12",
+        );
+        assert_eq!(
+            outline(form.widget.layout.as_ref().unwrap()),
+            "QGridLayout(fast@0,0 deep@0,1 path@1,0+1x2)"
+        );
+    }
+
+    #[test]
+    fn group_boxes_get_a_layout_of_their_own() {
+        let form = laid_out(
+            "GROUPBOX \"Options\", IDC_OPTIONS, 5, 5, 100, 40\n\
+             AUTOCHECKBOX \"Fast\", IDC_FAST, 10, 15, 40, 10\n\
+             AUTOCHECKBOX \"Deep\", IDC_DEEP, 10, 28, 40, 10",
+        );
+        let layout = form.widget.layout.as_ref().unwrap();
+        let UiLayoutContent::Widget(options) = &layout.items[0].content else {
+            panic!("{}", outline(layout));
+        };
+        assert_eq!(outline(options.layout.as_ref().unwrap()), "QGridLayout(fast@0,0 deep@1,0)");
+    }
+
+    #[test]
+    fn overlapping_controls_keep_their_geometry() {
+        let form = laid_out(
+            "EDITTEXT IDC_NAME, 5, 5, 100, 12\n\
+             LTEXT \"Name\", IDC_STATIC, 50, 8, 40, 8",
+        );
+        assert!(form.widget.layout.is_none());
+        assert!(form.widget.children.iter().all(|w| w.get("geometry").is_some()));
+    }
+}
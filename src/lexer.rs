@@ -0,0 +1,305 @@
+use crate::diagnostics::{Diagnostics, Span};
+use crate::preprocessor::SourceLine;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Ident(String),
+    Number(i64),
+    String(String),
+    Punct(char),
+    /// `BEGIN` or `{`.
+    Begin,
+    /// `END` or `}`.
+    End,
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+pub struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    // Where each line of the preprocessed text came from.
+    origins: &'a [SourceLine],
+    diagnostics: &'a mut Diagnostics,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str, origins: &'a [SourceLine], diagnostics: &'a mut Diagnostics) -> Self {
+        Lexer {
+            chars: source.chars().peekable(),
+            origins,
+            diagnostics,
+            line: 0,
+            column: 1,
+        }
+    }
+
+    /// Splits the whole input into tokens, ending with `TokenKind::Eof`.
+    /// Lexical errors are reported and the offending characters skipped.
+    pub fn tokenize(mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        loop {
+            self.skip_trivia();
+            let (line, column) = (self.line, self.column);
+            let Some(c) = self.bump() else {
+                tokens.push(Token {
+                    kind: TokenKind::Eof,
+                    span: self.span(line, column),
+                });
+                return tokens;
+            };
+
+            let kind = match c {
+                '"' => TokenKind::String(self.string(line, column)),
+                'L' | 'l' if self.chars.peek() == Some(&'"') => {
+                    self.bump();
+                    TokenKind::String(self.string(line, column))
+                }
+                '0'..='9' => match self.number(c) {
+                    Some(n) => TokenKind::Number(n),
+                    None => {
+                        let span = self.span(line, column);
+                        self.diagnostics.error(Some(span), "invalid number");
+                        TokenKind::Number(0)
+                    }
+                },
+                c if c.is_ascii_alphabetic() || c == '_' => {
+                    let mut ident = c.to_string();
+                    while let Some(&c) = self.chars.peek() {
+                        if !c.is_ascii_alphanumeric() && c != '_' {
+                            break;
+                        }
+                        ident.push(c);
+                        self.bump();
+                    }
+                    if ident.eq_ignore_ascii_case("BEGIN") {
+                        TokenKind::Begin
+                    } else if ident.eq_ignore_ascii_case("END") {
+                        TokenKind::End
+                    } else {
+                        TokenKind::Ident(ident)
+                    }
+                }
+                '{' => TokenKind::Begin,
+                '}' => TokenKind::End,
+                ',' | '|' | '&' | '+' | '-' | '*' | '/' | '~' | '(' | ')' | '=' | ';' => {
+                    TokenKind::Punct(c)
+                }
+                _ => {
+                    let span = self.span(line, column);
+                    self.diagnostics
+                        .error(Some(span), format!("unexpected character '{}'", c));
+                    continue;
+                }
+            };
+            tokens.push(Token {
+                kind,
+                span: self.span(line, column),
+            });
+        }
+    }
+
+    // Maps a token that started at (line, column) and ends at the current
+    // position back to its original file.
+    fn span(&self, line: usize, column: usize) -> Span {
+        let origin = self
+            .origins
+            .get(line)
+            .or(self.origins.last())
+            .copied()
+            .unwrap_or(SourceLine { file: 0, line: 1 });
+        Span {
+            file: origin.file,
+            line: origin.line,
+            column,
+            len: if self.line == line {
+                self.column.saturating_sub(column).max(1)
+            } else {
+                1
+            },
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_trivia(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() {
+                self.bump();
+                continue;
+            }
+            if c != '/' {
+                break;
+            }
+            let mut ahead = self.chars.clone();
+            ahead.next();
+            match ahead.next() {
+                Some('/') => {
+                    while self.chars.peek().is_some_and(|&c| c != '\n') {
+                        self.bump();
+                    }
+                }
+                Some('*') => {
+                    let (line, column) = (self.line, self.column);
+                    self.bump();
+                    self.bump();
+                    loop {
+                        match self.bump() {
+                            Some('*') if self.chars.peek() == Some(&'/') => {
+                                self.bump();
+                                break;
+                            }
+                            Some(_) => {}
+                            None => {
+                                let span = self.span(line, column);
+                                self.diagnostics.error(Some(span), "unterminated comment");
+                                return;
+                            }
+                        }
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    // Reads a quoted string after its opening quote. `""` stands for a quote.
+    fn string(&mut self, line: usize, column: usize) -> String {
+        let mut text = String::new();
+        loop {
+            match self.bump() {
+                Some('"') if self.chars.peek() == Some(&'"') => {
+                    self.bump();
+                    text.push('"');
+                }
+                Some('"') => return text,
+                Some('\\') => match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some('r') => text.push('\r'),
+                    Some('a') => text.push('\x07'),
+                    // Up to three octal digits, as in "\000".
+                    Some(c @ '0'..='7') => {
+                        let mut value = c.to_digit(8).unwrap_or(0);
+                        for _ in 0..2 {
+                            match self.chars.peek().and_then(|c| c.to_digit(8)) {
+                                Some(digit) => {
+                                    value = value * 8 + digit;
+                                    self.bump();
+                                }
+                                None => break,
+                            }
+                        }
+                        text.extend(char::from_u32(value));
+                    }
+                    Some('x') | Some('X') => {
+                        let mut digits = String::new();
+                        while digits.len() < 4 && self.chars.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                            digits.extend(self.bump());
+                        }
+                        let value = u32::from_str_radix(&digits, 16).unwrap_or(0);
+                        text.extend(char::from_u32(value));
+                    }
+                    Some(c) => text.push(c),
+                    None => break,
+                },
+                Some('\n') | None => break,
+                Some(c) => text.push(c),
+            }
+        }
+        let span = self.span(line, column);
+        self.diagnostics.error(Some(span), "unterminated string");
+        text
+    }
+
+    // Decimal or `0x` hexadecimal, with optional `L`/`U` suffixes.
+    fn number(&mut self, first: char) -> Option<i64> {
+        let mut text = first.to_string();
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_ascii_alphanumeric() {
+                break;
+            }
+            text.push(c);
+            self.bump();
+        }
+        let digits = text.trim_end_matches(['l', 'L', 'u', 'U']);
+        match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+            Some(hex) => i64::from_str_radix(hex, 16).ok(),
+            None => digits.parse().ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> (Vec<TokenKind>, Diagnostics) {
+        let mut diagnostics = Diagnostics::new();
+        let tokens = Lexer::new(source, &[], &mut diagnostics).tokenize();
+        (tokens.into_iter().map(|t| t.kind).collect(), diagnostics)
+    }
+
+    fn string(source: &str) -> String {
+        match kinds(source).0.remove(0) {
+            TokenKind::String(text) => text,
+            other => panic!("expected a string, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn string_escapes() {
+        assert_eq!(string(r#""a\tb\nc""#), "a\tb\nc");
+        assert_eq!(string(r#""say ""hi""""#), "say \"hi\"");
+        assert_eq!(string(r#""\000""#), "\0");
+        assert_eq!(string(r#""\101\1012""#), "AA2");
+        assert_eq!(string(r#""\x41\x263A""#), "A\u{263A}");
+        assert_eq!(string(r#"L"wide""#), "wide");
+    }
+
+    #[test]
+    fn unterminated_string_is_an_error() {
+        let (_, diagnostics) = kinds("\"open\nEND");
+        assert!(diagnostics.has_errors());
+    }
+
+    #[test]
+    fn braces_and_begin_end_are_the_same_tokens() {
+        let (braces, _) = kinds("{ 1 }");
+        let (words, _) = kinds("BEGIN 1 end");
+        assert_eq!(braces, words);
+        assert_eq!(braces, vec![TokenKind::Begin, TokenKind::Number(1), TokenKind::End, TokenKind::Eof]);
+    }
+
+    #[test]
+    fn numeric_suffixes() {
+        let (tokens, diagnostics) = kinds("10L 0x10UL 5u 0XffL");
+        assert_eq!(
+            tokens,
+            vec![
+                TokenKind::Number(10),
+                TokenKind::Number(16),
+                TokenKind::Number(5),
+                TokenKind::Number(255),
+                TokenKind::Eof
+            ]
+        );
+        assert!(!diagnostics.has_errors());
+        assert!(kinds("12abc").1.has_errors());
+    }
+}
@@ -0,0 +1,108 @@
+//! Converts Windows resource scripts (`.rc`) to Qt project files.
+//!
+//! A script is parsed into a `ResourceScript`, which the functions in
+//! `generator` turn into `.ui`, `.qrc` and C++ output.
+
+pub mod ast;
+pub mod diagnostics;
+pub mod dialog_units;
+mod error;
+pub mod form;
+pub mod generator;
+mod layout;
+mod lexer;
+mod parser;
+pub mod preprocessor;
+pub mod resource;
+pub mod styles;
+mod symbols;
+pub mod ui;
+mod widgets;
+
+pub use diagnostics::Diagnostics;
+pub use error::{Error, Result};
+pub use preprocessor::PreprocessOptions;
+pub use resource::ResourceScript;
+
+use diagnostics::Severity;
+use preprocessor::{PreprocessedSource, Preprocessor};
+use std::io::Read;
+use std::path::Path;
+
+/// Parses the script at `path`. Errors and warnings are recorded in
+/// `diagnostics`; if there are errors, `Error::Parse` is returned.
+pub fn parse_file<P: AsRef<Path>>(
+    path: P,
+    options: &PreprocessOptions,
+    diagnostics: &mut Diagnostics,
+) -> Result<ResourceScript> {
+    let source = Preprocessor::new(options, diagnostics).process_file(path)?;
+    parse(&source, diagnostics)
+}
+
+/// Parses a script held in a string. Quoted `#include`s are searched in the
+/// current directory and in the include paths.
+pub fn parse_str(
+    source: &str,
+    options: &PreprocessOptions,
+    diagnostics: &mut Diagnostics,
+) -> Result<ResourceScript> {
+    let source = Preprocessor::new(options, diagnostics).process_str("<string>", source);
+    parse(&source, diagnostics)
+}
+
+/// Parses a script read from `reader`, in any of the encodings accepted for files.
+pub fn parse_reader<R: Read>(
+    mut reader: R,
+    options: &PreprocessOptions,
+    diagnostics: &mut Diagnostics,
+) -> Result<ResourceScript> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let source = Preprocessor::new(options, diagnostics).process_bytes("<input>", &bytes);
+    parse(&source, diagnostics)
+}
+
+fn parse(source: &PreprocessedSource, diagnostics: &mut Diagnostics) -> Result<ResourceScript> {
+    let script = parser::parse_source(source, diagnostics);
+    match diagnostics.count(Severity::Error) {
+        0 => Ok(script),
+        errors => Err(Error::Parse { errors }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = "IDI_APP ICON \"res\\\\app.ico\"\nIDB_LOGO BITMAP \"res\\\\logo.bmp\"\n";
+
+    #[test]
+    fn parse_errors_are_returned_with_their_count() {
+        let mut diagnostics = Diagnostics::new();
+        let result = parse_str("1 MENU\nBEGIN\n  MENUITEM 5\nEND\n", &PreprocessOptions::default(), &mut diagnostics);
+        assert!(matches!(result, Err(Error::Parse { errors: 1 })));
+        assert!(diagnostics.has_errors());
+    }
+
+    #[test]
+    fn utf16_input_parses_like_text() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(SCRIPT.encode_utf16().flat_map(u16::to_le_bytes));
+        let mut diagnostics = Diagnostics::new();
+        let script = parse_reader(&bytes[..], &PreprocessOptions::default(), &mut diagnostics).unwrap();
+        assert_eq!(script.resources().len(), 2);
+        assert!(script.get("ICON", "IDI_APP").is_some());
+    }
+
+    #[test]
+    fn generators_write_to_any_writer() {
+        let mut diagnostics = Diagnostics::new();
+        let script = parse_str(SCRIPT, &PreprocessOptions::default(), &mut diagnostics).unwrap();
+        let mut qrc = Vec::new();
+        generator::write_qrc(&script, &mut qrc).unwrap();
+        let qrc = String::from_utf8(qrc).unwrap();
+        assert!(qrc.contains("<file>res/app.ico</file>"));
+        assert!(qrc.contains("<file>res/logo.bmp</file>"));
+    }
+}
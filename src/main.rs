@@ -1,13 +1,18 @@
 extern crate env_logger;
 extern crate log;
 
-mod generator;
-mod parser;
-mod resource;
-
 use clap::{Arg, Command};
+use rc2qt::dialog_units::parse_font_metric;
+use rc2qt::form::{FormOptions, PromotionRule};
+use rc2qt::{generator, Diagnostics, Error, PreprocessOptions, ResourceScript};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process;
 
 fn main() {
+    env_logger::init();
+
     let matches = Command::new("rc2qt")
         .version("0.01")
         .author("Guillaume Gielly")
@@ -24,39 +29,138 @@ fn main() {
                 .required(true)
                 .index(2),
         )
+        .arg(
+            Arg::new("include")
+                .short('i')
+                .long("include")
+                .help("Adds a directory to the #include search path")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("define")
+                .short('D')
+                .long("define")
+                .help("Defines a preprocessor macro, as NAME or NAME=VALUE")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("naming")
+                .long("naming")
+                .help("How dialog IDs become .ui names: camel (AboutBox.ui), lower (aboutbox.ui) or id (IDD_ABOUTBOX.ui)")
+                .takes_value(true)
+                .possible_values(["camel", "lower", "id"])
+                .default_value("camel"),
+        )
+        .arg(
+            Arg::new("font-metric")
+                .long("font-metric")
+                .help("Sets the average character size of a dialog font, as FACE,POINTS,WIDTH,HEIGHT in pixels")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(
+            Arg::new("promote-class")
+                .long("promote-class")
+                .help("Names the Qt class that replaces an unknown window class; {class} is the window class")
+                .takes_value(true)
+                .default_value("{class}"),
+        )
+        .arg(
+            Arg::new("promote-header")
+                .long("promote-header")
+                .help("Names the header of a promoted class; {class} is the class, {lower} the class in lowercase")
+                .takes_value(true)
+                .default_value("{lower}.h"),
+        )
+        .arg(
+            Arg::new("layouts")
+                .long("layouts")
+                .help("Arranges dialog controls in layouts inferred from their positions instead of fixed geometry"),
+        )
         .get_matches();
 
     let rc_file_path = matches.get_one::<String>("rcfile").unwrap();
     let output_dir = matches.get_one::<String>("output").unwrap();
+    let mut form_options = FormOptions {
+        naming: matches
+            .get_one::<String>("naming")
+            .unwrap()
+            .parse()
+            .unwrap(),
+        promotion: PromotionRule {
+            class: matches.get_one::<String>("promote-class").unwrap().clone(),
+            header: matches.get_one::<String>("promote-header").unwrap().clone(),
+        },
+        layouts: matches.contains_id("layouts"),
+        ..FormOptions::default()
+    };
+    for spec in matches.get_many::<String>("font-metric").into_iter().flatten() {
+        match parse_font_metric(spec) {
+            Ok((face, point_size, units)) => form_options.font_metrics.insert(&face, point_size, units),
+            Err(e) => {
+                eprintln!("error: invalid --font-metric: {}", e);
+                process::exit(2);
+            }
+        }
+    }
+
+    let mut options = PreprocessOptions::default();
+    if let Some(paths) = matches.get_many::<String>("include") {
+        options.include_paths = paths.map(PathBuf::from).collect();
+    }
+    if let Some(defines) = matches.get_many::<String>("define") {
+        options.defines = defines
+            .map(|d| match d.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None => (d.to_string(), "1".to_string()),
+            })
+            .collect();
+    }
+
+    let mut diagnostics = Diagnostics::new();
+    let script = match rc2qt::parse_file(rc_file_path, &options, &mut diagnostics) {
+        Ok(script) => script,
+        // The diagnostics explain what went wrong.
+        Err(Error::Parse { .. }) => {
+            eprint!("{}", diagnostics.render());
+            process::exit(1);
+        }
+        Err(e) => {
+            eprint!("{}", diagnostics.render());
+            eprintln!("error: cannot read '{}': {}", rc_file_path, e);
+            process::exit(1);
+        }
+    };
+
+    let result = generate(&script, output_dir, &form_options, &mut diagnostics);
+    eprint!("{}", diagnostics.render());
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn generate(
+    script: &ResourceScript,
+    output_dir: &str,
+    options: &FormOptions,
+    diagnostics: &mut Diagnostics,
+) -> rc2qt::Result<()> {
+    fs::create_dir_all(output_dir)?;
+    let mut out = io::stdout().lock();
+    generator::output_bitmaps(script, &mut out)?;
+    generator::output_icons(script, &mut out)?;
+    generator::output_string_tables(script, &mut out)?;
+    generator::output_toolbars(script, &mut out)?;
+    generator::output_accelerators(script, &mut out)?;
+    generator::output_menus(script, &mut out)?;
+    generator::output_dialogs(script, &mut out)?;
+
+    let qrc_output_path = format!("{}/{}", output_dir, generator::QRC_FILE_NAME);
+    generator::create_qrc_file(script, &qrc_output_path)?;
 
-    let (
-        bitmap_list,
-        icon_list,
-        string_table_list,
-        toolbar_list,
-        accelerator_table_list,
-        menu_list,
-        dialog_ex_list,
-    ) = parser::parse_rc_file(rc_file_path);
-
-    // Output each list
-    bitmap_list.output();
-    icon_list.output();
-    string_table_list.output();
-    toolbar_list.output();
-    accelerator_table_list.output();
-    menu_list.output();
-    dialog_ex_list.output();
-
-    // Example for creating a .qrc file:
-    let resources: std::collections::HashMap<String, String> = std::collections::HashMap::new(); // Populate this with actual resource data
-    let qrc_output_path = format!("{}/resources.qrc", output_dir);
-    generator::create_qrc_file(&resources, &qrc_output_path);
-
-    // Example for creating a .ui file:
-    let dialog_name = "ExampleDialog";
-    let controls =
-        vec!["CONTROL \"Button\", IDC_BUTTON1, BUTTON, WS_TABSTOP, 10, 10, 50, 14".to_string()];
-    let output_path = format!("{}/{}.ui", output_dir, dialog_name);
-    generator::create_ui_file(dialog_name, &controls, &output_path);
+    generator::create_ui_files(script, output_dir, options, diagnostics)?;
+    Ok(())
 }
@@ -1,212 +1,1355 @@
-use regex::Regex;
-use std::io::{self, BufRead};
-use std::path::Path;
+use crate::ast::*;
+use crate::diagnostics::{Diagnostics, Span};
+use crate::lexer::{Lexer, Token, TokenKind};
+use crate::preprocessor::PreprocessedSource;
 use crate::resource::*;
-use log::{info, warn, error}; // Import logging macros
-
-pub fn parse_rc_file(file_path: &str) -> (
-    RcBitmapList, RcIconList, RcStringTableList, RcToolbarList, RcAcceleratorTableList, RcMenuList, RcDialogExList
-) {
-    let mut bitmap_list = RcBitmapList::new();
-    let mut icon_list = RcIconList::new();
-    let mut string_table_list = RcStringTableList::new();
-    let mut toolbar_list = RcToolbarList::new();
-    let mut accelerator_table_list = RcAcceleratorTableList::new();
-    let mut menu_list = RcMenuList::new();
-    let mut dialog_ex_list = RcDialogExList::new();
-
-    if let Ok(mut lines) = read_lines(file_path) {
-        while let Some(Ok(line)) = lines.next() {
-            let line = line.trim();
-
-            // Example for bitmaps:
-            let bitmap_re = Regex::new(r#"(\w+)\s+BITMAP\s+"(.+)""#).unwrap();
-            if let Some(caps) = bitmap_re.captures(line) {
-                let id = caps[1].to_string();
-                let file = caps[2].to_string();
-
-                // Logging information
-                info!("Parsing bitmap: ID = {}, File = {}", id, file);
+use crate::styles;
+use crate::symbols::{check_collisions, SymbolTable};
+use log::info;
 
+const MEMORY_FLAGS: &[&str] = &[
+    "PRELOAD", "LOADONCALL", "FIXED", "MOVEABLE", "DISCARDABLE", "PURE", "IMPURE", "SHARED",
+    "NONSHARED",
+];
+
+const MENU_OPTIONS: &[&str] = &["CHECKED", "GRAYED", "INACTIVE", "MENUBREAK", "MENUBARBREAK", "HELP"];
+
+// Resource types used to find the start of the next resource after an error.
+const RESOURCE_TYPES: &[&str] = &[
+    "ACCELERATORS", "BITMAP", "CURSOR", "DIALOG", "DIALOGEX", "DLGINIT", "FONT", "HTML", "ICON",
+    "MENU", "MENUEX", "MESSAGETABLE", "RCDATA", "TEXTINCLUDE", "TOOLBAR", "VERSIONINFO",
+];
+
+struct ParseError {
+    message: String,
+    span: Span,
+}
+
+/// Parses a preprocessed script, recording every problem in `diagnostics`.
+/// Resources that fail to parse are skipped; the rest of the script is still
+/// converted.
+pub fn parse_source(source: &PreprocessedSource, diagnostics: &mut Diagnostics) -> ResourceScript {
+    let mut script = ResourceScript::new();
+
+    info!(
+        "Preprocessed {} line(s), {} macro(s) defined",
+        source.lines.len(),
+        source.macros.len()
+    );
+    if let Some(code_page) = source.code_page {
+        info!("Script uses code page {}", code_page);
+    }
+
+    let tokens = Lexer::new(&source.text, &source.lines, diagnostics).tokenize();
+    let items = Parser::new(tokens, diagnostics).script().items;
+
+    // Symbolic IDs are resolved through the #defines seen by the preprocessor.
+    let symbols = SymbolTable::from_macros(&source.macros);
+
+    let mut language = None;
+    for item in items {
+        let resource = match item {
+            Item::Language { primary, sub, span } => {
+                info!("LANGUAGE {}, {} at line {}", primary, sub, span.line);
+                language = Some(language_of(&primary, &sub, &symbols));
+                continue;
+            }
+            Item::Resource(resource) => resource,
+        };
+        let span = resource.span;
+        let id = match &resource.id {
+            Some(id) => symbols.resolve_id(&id.to_string(), span, diagnostics),
+            None => RcId::default(),
+        };
+
+        let data = match resource.body {
+            ResourceBody::File(file) if resource.type_name == "BITMAP" => {
                 // Basic validation: check if file path ends with ".bmp"
-                if !file.ends_with(".bmp") {
-                    warn!("Bitmap file '{}' does not have a .bmp extension", file);
+                if !file.to_ascii_lowercase().ends_with(".bmp") {
+                    diagnostics.warning(
+                        Some(span),
+                        format!("Bitmap file '{}' does not have a .bmp extension", file),
+                    );
                 }
 
-                // Optional: Normalize file paths, e.g., replace backslashes with forward slashes
-                let normalized_file = file.replace("\\", "/");
-
-                // Extract additional attributes
-                let mut width: Option<u32> = None;
-                let mut height: Option<u32> = None;
-                let mut color_depth: Option<u32> = None;
-                let mut compression: Option<String> = None;
-                let mut palette: Option<String> = None;
-                let mut dpi: Option<u32> = None;
-                let mut color_mode: Option<String> = None;
-                let mut compression_level: Option<u32> = None;
-                let mut author: Option<String> = None;
-
-                // Look ahead for additional attributes
-                while let Some(Ok(attr_line)) = lines.next() {
-                    let attr_line = attr_line.trim();
-                    if attr_line.starts_with("END") {
-                        break;
+                RcResourceData::Bitmap(RcBitmap {
+                    file: file.replace('\\', "/"),
+                    width: None,
+                    height: None,
+                    color_depth: None,
+                    compression: None,
+                    palette: None,
+                    dpi: None,
+                    color_mode: None,
+                    compression_level: None,
+                    author: None,
+                })
+            }
+            ResourceBody::File(file) if resource.type_name == "ICON" => {
+                RcResourceData::Icon(RcIcon { file })
+            }
+            ResourceBody::File(file) => RcResourceData::File(file),
+            ResourceBody::Data(data) if resource.type_name == "DLGINIT" => {
+                RcResourceData::DialogInit(dialog_init(&data, span, &symbols, diagnostics))
+            }
+            ResourceBody::Data(data) => RcResourceData::Data(data),
+            ResourceBody::StringTable(entries) => RcResourceData::StringTable(RcStringTable::new(
+                entries
+                    .into_iter()
+                    .map(|entry| RcStringTableItem {
+                        id: symbols.resolve_id(&entry.id.to_string(), entry.span, diagnostics),
+                        text: entry.text,
+                        span: entry.span,
+                    })
+                    .collect(),
+            )),
+            ResourceBody::Toolbar(toolbar) => RcResourceData::Toolbar(RcToolbar {
+                numbers: [&toolbar.width, &toolbar.height]
+                    .iter()
+                    .filter_map(|e| symbols.eval(e))
+                    .map(|n| n as i32)
+                    .collect(),
+                table: toolbar
+                    .buttons
+                    .iter()
+                    .map(|button| match button {
+                        Some(id) => format!("BUTTON {}", id),
+                        None => "SEPARATOR".to_string(),
+                    })
+                    .collect(),
+            }),
+            ResourceBody::Accelerators(entries) => {
+                for entry in &entries {
+                    if let Some(command) = entry.args.get(1) {
+                        symbols.resolve_id(&command.to_string(), entry.span, diagnostics);
                     }
+                }
+                RcResourceData::Accelerators(RcAcceleratorTable {
+                    table: entries
+                        .iter()
+                        .map(|entry| join_args(&entry.args))
+                        .collect(),
+                })
+            }
+            ResourceBody::Menu(items) => {
+                let mut table = Vec::new();
+                flatten_menu(&items, 0, &mut table);
+                check_menu_ids(&items, &symbols, diagnostics);
+                RcResourceData::Menu(RcMenu { table })
+            }
+            ResourceBody::Dialog(dialog) => RcResourceData::Dialog(dialog_header(
+                resource.type_name == "DIALOGEX",
+                &dialog,
+                &resource.options,
+                span,
+                &symbols,
+                diagnostics,
+            )),
+            ResourceBody::VersionInfo(version) => RcResourceData::VersionInfo(version),
+        };
 
-                    if let Some(caps) = Regex::new(r#"WIDTH\s+(\d+)"#).unwrap().captures(attr_line) {
-                        width = Some(caps[1].parse().unwrap());
-                    } else if let Some(caps) = Regex::new(r#"HEIGHT\s+(\d+)"#).unwrap().captures(attr_line) {
-                        height = Some(caps[1].parse().unwrap());
-                    } else if let Some(caps) = Regex::new(r#"COLOR_DEPTH\s+(\d+)"#).unwrap().captures(attr_line) {
-                        color_depth = Some(caps[1].parse().unwrap());
-                    } else if let Some(caps) = Regex::new(r#"COMPRESSION\s+(.+)"#).unwrap().captures(attr_line) {
-                        compression = Some(caps[1].to_string());
-                    } else if let Some(caps) = Regex::new(r#"PALETTE\s+(.+)"#).unwrap().captures(attr_line) {
-                        palette = Some(caps[1].to_string());
-                    } else if let Some(caps) = Regex::new(r#"DPI\s+(\d+)"#).unwrap().captures(attr_line) {
-                        dpi = Some(caps[1].parse().unwrap());
-                    } else if let Some(caps) = Regex::new(r#"COLOR_MODE\s+(.+)"#).unwrap().captures(attr_line) {
-                        color_mode = Some(caps[1].to_string());
-                    } else if let Some(caps) = Regex::new(r#"COMPRESSION_LEVEL\s+(\d+)"#).unwrap().captures(attr_line) {
-                        compression_level = Some(caps[1].parse().unwrap());
-                    } else if let Some(caps) = Regex::new(r#"AUTHOR\s+(.+)"#).unwrap().captures(attr_line) {
-                        author = Some(caps[1].to_string());
-                    } else {
-                        warn!("Unknown attribute for bitmap '{}': {}", id, attr_line);
-                    }
+        // A LANGUAGE statement among the options applies to this resource only.
+        let resource_language = match resource
+            .options
+            .iter()
+            .find(|o| o.keyword == "LANGUAGE" && o.args.len() == 2)
+        {
+            Some(option) => Some(language_of(&option.args[0], &option.args[1], &symbols)),
+            None => language.clone(),
+        };
+        // The same ID may be defined once per language.
+        let mut keys = vec![id.name.clone()];
+        keys.extend(id.value.map(|v| v.to_string()));
+        if resource.id.is_some()
+            && keys
+                .iter()
+                .any(|key| script.get_in(&resource.type_name, key, resource_language.as_ref()).is_some())
+        {
+            diagnostics.warning(
+                Some(span),
+                format!("duplicate {} resource {}", resource.type_name, id),
+            );
+        }
+        script.add(RcResource {
+            id,
+            type_name: resource.type_name,
+            language: resource_language,
+            memory_flags: resource.memory_flags,
+            span,
+            data,
+        });
+    }
+
+    for resource in script.resources() {
+        let flags: String = resource.memory_flags.iter().map(|f| format!(" {}", f)).collect();
+        match (&resource.data, &resource.language) {
+            (RcResourceData::VersionInfo(version), _) => {
+                for statement in &version.fixed {
+                    info!("VERSIONINFO {}", statement);
                 }
+                log_version_blocks(&version.blocks, "");
+            }
+            (RcResourceData::DialogInit(init), _) => info!(
+                "{} resource {}{} ({} control setting(s))",
+                resource.type_name,
+                resource.id,
+                flags,
+                init.entries.len()
+            ),
+            (RcResourceData::Data(data), _) => info!(
+                "{} resource {}{} ({} data item(s))",
+                resource.type_name,
+                resource.id,
+                flags,
+                data.len()
+            ),
+            (_, Some(language)) => info!(
+                "{} resource {}{} (LANGUAGE {})",
+                resource.type_name, resource.id, flags, language
+            ),
+            (_, None) => info!("{} resource {}{}", resource.type_name, resource.id, flags),
+        }
+    }
 
-                // Add bitmap to the list
-                bitmap_list.add(RcBitmap {
-                    id,
-                    file: normalized_file,
-                    width,
-                    height,
-                    color_depth,
-                    compression,
-                    palette,
-                    dpi,
-                    color_mode,
-                    compression_level,
-                    author,
-                });
+    for (kind, types) in [
+        ("Bitmap", &["BITMAP"][..]),
+        ("Icon", &["ICON"]),
+        ("Toolbar", &["TOOLBAR"]),
+        ("Accelerator table", &["ACCELERATORS"]),
+        ("Menu", &["MENU", "MENUEX"]),
+        ("Dialog", &["DIALOG", "DIALOGEX"]),
+    ] {
+        check_collisions(
+            kind,
+            types
+                .iter()
+                .flat_map(|t| script.of_type(t))
+                .map(|r| (&r.id, r.span)),
+            diagnostics,
+        );
+    }
+    check_collisions(
+        "String",
+        script
+            .string_tables()
+            .flat_map(|(_, t)| t.table.iter().map(|i| (&i.id, i.span))),
+        diagnostics,
+    );
 
-                continue;
+    script
+}
+
+fn language_of(primary: &Expr, sub: &Expr, symbols: &SymbolTable) -> RcLanguage {
+    let to_id = |e: &Expr| RcId {
+        name: e.to_string(),
+        value: symbols.eval(e),
+    };
+    RcLanguage {
+        primary: to_id(primary),
+        sub: to_id(sub),
+    }
+}
+
+// Reads the rectangle and optional statements of a DIALOG or DIALOGEX.
+fn dialog_header(
+    extended: bool,
+    dialog: &DialogDef,
+    options: &[Statement],
+    span: Span,
+    symbols: &SymbolTable,
+    diagnostics: &mut Diagnostics,
+) -> RcDialogEx {
+    let mut rect = [0; 4];
+    for (i, value) in rect.iter_mut().enumerate() {
+        match dialog.params.get(i).map(|e| (e, symbols.eval(e))) {
+            Some((_, Some(v))) => *value = v as i32,
+            Some((e, None)) => diagnostics.warning(
+                Some(span),
+                format!("dialog coordinate '{}' is not a number", e),
+            ),
+            None => {
+                diagnostics.error(Some(span), "dialog needs x, y, width and height");
+                break;
             }
+        }
+    }
+    let [x, y, width, height] = rect;
+    let help_id = match dialog.params.get(4) {
+        Some(e) if extended => symbols.eval(e),
+        Some(_) => {
+            diagnostics.warning(Some(span), "help ID is only allowed in DIALOGEX");
+            None
+        }
+        None => None,
+    };
+
+    let mut header = RcDialogEx {
+        extended,
+        x,
+        y,
+        width,
+        height,
+        help_id,
+        caption: String::new(),
+        style: None,
+        effective_style: 0,
+        ex_style: None,
+        effective_ex_style: 0,
+        font: None,
+        menu: None,
+        class: None,
+        controls: dialog
+            .controls
+            .iter()
+            .filter_map(|c| dialog_control(c, symbols, diagnostics))
+            .collect(),
+    };
 
-            // Example for icons:
-            let icon_re = Regex::new(r#"(\w+)\s+ICON\s+"(.+)""#).unwrap();
-            if let Some(caps) = icon_re.captures(line) {
-                icon_list.add(RcIcon {
-                    id: caps[1].to_string(),
-                    file: caps[2].to_string(),
+    let mut style_span = span;
+    let mut captioned = false;
+    for option in options {
+        let args = &option.args;
+        match (option.keyword.as_str(), args.first()) {
+            ("CAPTION", Some(Expr::String(text))) => {
+                header.caption = text.clone();
+                captioned = true;
+            }
+            ("STYLE", Some(style)) => {
+                header.style = Some(style.clone());
+                style_span = option.span;
+            }
+            ("EXSTYLE", Some(style)) => {
+                header.ex_style = Some(style.clone());
+                header.effective_ex_style = styles::apply_style(0, style, symbols, option.span, diagnostics);
+            }
+            ("FONT", Some(size)) => {
+                let number = |i: usize| args.get(i).and_then(|e| symbols.eval(e)).unwrap_or(0) as i32;
+                let face = match args.get(1) {
+                    Some(Expr::String(face)) => face.clone(),
+                    _ => {
+                        diagnostics.warning(Some(option.span), "FONT needs a point size and a typeface");
+                        continue;
+                    }
+                };
+                header.font = Some(RcFont {
+                    point_size: symbols.eval(size).unwrap_or(0) as i32,
+                    face,
+                    weight: number(2),
+                    italic: number(3) != 0,
+                    charset: number(4),
                 });
-                continue;
             }
+            ("MENU", Some(menu)) => {
+                header.menu = Some(symbols.resolve_id(&menu.to_string(), option.span, diagnostics))
+            }
+            ("CLASS", Some(class)) => {
+                header.class = Some(match class {
+                    Expr::String(name) => name.clone(),
+                    other => other.to_string(),
+                })
+            }
+            // Handled for every resource type, or irrelevant to Qt.
+            ("LANGUAGE" | "CHARACTERISTICS" | "VERSION", _) => {}
+            _ => diagnostics.warning(
+                Some(option.span),
+                format!("ignoring unsupported dialog statement '{}'", option),
+            ),
+        }
+    }
+    header.effective_style = styles::dialog_style(
+        header.style.as_ref(),
+        captioned,
+        header.font.is_some(),
+        symbols,
+        style_span,
+        diagnostics,
+    );
+    header
+}
+
+// Converts a control statement. Its arguments are, by keyword:
+//   CONTROL    text, id, class, style, x, y, cx, cy [, exstyle [, helpid]]
+//   ICON       text, id, x, y [, cx, cy [, style [, exstyle [, helpid]]]]
+//   EDITTEXT,
+//   COMBOBOX,
+//   LISTBOX,
+//   SCROLLBAR  id, x, y, cx, cy [, style [, exstyle [, helpid]]]
+//   others     text, id, x, y, cx, cy [, style [, exstyle [, helpid]]]
+fn dialog_control(
+    statement: &Statement,
+    symbols: &SymbolTable,
+    diagnostics: &mut Diagnostics,
+) -> Option<DialogControl> {
+    let kind = statement.keyword.as_str();
+    let class = match kind {
+        "CONTROL" => "",
+        "LTEXT" | "RTEXT" | "CTEXT" | "ICON" => "Static",
+        "PUSHBUTTON" | "DEFPUSHBUTTON" | "PUSHBOX" | "CHECKBOX" | "AUTOCHECKBOX" | "RADIOBUTTON"
+        | "AUTORADIOBUTTON" | "STATE3" | "AUTO3STATE" | "GROUPBOX" => "Button",
+        "EDITTEXT" => "Edit",
+        "COMBOBOX" => "ComboBox",
+        "LISTBOX" => "ListBox",
+        "SCROLLBAR" => "ScrollBar",
+        _ => {
+            diagnostics.warning(
+                Some(statement.span),
+                format!("ignoring unknown control statement '{}'", kind),
+            );
+            return None;
+        }
+    };
+
+    let mut args = statement.args.iter();
+    let has_text = !matches!(kind, "EDITTEXT" | "COMBOBOX" | "LISTBOX" | "SCROLLBAR");
+    let text = if has_text {
+        match args.next() {
+            Some(Expr::String(text)) => text.clone(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        }
+    } else {
+        String::new()
+    };
+    let Some(id) = args.next() else {
+        diagnostics.error(Some(statement.span), format!("{} needs an ID", kind));
+        return None;
+    };
+    let id = symbols.resolve_expr(id, statement.span, diagnostics);
+
+    let (class, mut style) = if kind == "CONTROL" {
+        let class = match args.next() {
+            Some(Expr::String(name)) | Some(Expr::Symbol(name)) => Some(name.clone()),
+            Some(Expr::Number(atom)) => predefined_class(*atom).map(str::to_string),
+            _ => None,
+        };
+        let Some(class) = class else {
+            diagnostics.error(Some(statement.span), "CONTROL needs a window class");
+            return None;
+        };
+        (class, args.next().cloned())
+    } else {
+        (class.to_string(), None)
+    };
+
+    let mut rect = [0; 4];
+    let required = if kind == "ICON" { 2 } else { 4 };
+    for (i, value) in rect.iter_mut().enumerate() {
+        match args.next() {
+            Some(e) => *value = symbols.eval(e).unwrap_or(0) as i32,
+            None if i >= required => break,
+            None => {
+                diagnostics.error(
+                    Some(statement.span),
+                    format!("{} needs x, y, width and height", kind),
+                );
+                return None;
+            }
+        }
+    }
+    let [x, y, width, height] = rect;
 
-            // Example for string tables:
-            let string_table_re = Regex::new(r#"\s*STRINGTABLE\s*BEGIN"#).unwrap();
-            if string_table_re.is_match(line) {
-                let mut string_items = Vec::new();
-                while let Some(Ok(line)) = lines.next() {
-                    let line = line.trim();
-                    if line == "END" {
-                        break;
-                    }
-                    let string_item_re = Regex::new(r#"(\w+)\s+"(.+)""#).unwrap();
-                    if let Some(caps) = string_item_re.captures(line) {
-                        string_items.push(RcStringTableItem {
-                            id: caps[1].to_string(),
-                            text: caps[2].to_string(),
-                        });
+    if kind != "CONTROL" {
+        style = args.next().cloned();
+    }
+    let ex_style = args.next().cloned();
+    let help_id = args.next().and_then(|e| symbols.eval(e));
+
+    let effective_style = styles::control_style(kind, style.as_ref(), symbols, statement.span, diagnostics);
+    let effective_ex_style = ex_style
+        .as_ref()
+        .map_or(0, |e| styles::apply_style(0, e, symbols, statement.span, diagnostics));
+
+    Some(DialogControl {
+        kind: kind.to_string(),
+        text,
+        id,
+        class,
+        style,
+        effective_style,
+        ex_style,
+        effective_ex_style,
+        x,
+        y,
+        width,
+        height,
+        help_id,
+        span: statement.span,
+    })
+}
+
+// Decodes the data of a DLGINIT resource: for each entry a control ID and a
+// message as WORDs, a DWORD data length and the data, up to a zero control
+// ID. Numbers in the script are WORDs and strings stand for their bytes.
+fn dialog_init(data: &[Expr], span: Span, symbols: &SymbolTable, diagnostics: &mut Diagnostics) -> RcDialogInit {
+    let mut bytes = Vec::new();
+    for expr in data {
+        match expr {
+            Expr::String(text) => {
+                for c in text.chars() {
+                    match u8::try_from(c) {
+                        Ok(b) => bytes.push(b),
+                        Err(_) => bytes.extend(c.to_string().as_bytes()),
                     }
                 }
-                string_table_list.add(RcStringTable::new(string_items));
-                continue;
             }
-
-            // Example for toolbars:
-            let toolbar_re = Regex::new(r#"(\w+)\s+TOOLBAR\s+"(.+)""#).unwrap();
-            if let Some(caps) = toolbar_re.captures(line) {
-                toolbar_list.add(RcToolbar {
-                    id: caps[1].to_string(),
-                    numbers: Vec::new(),
-                    table: vec![caps[2].to_string()],
+            _ => {
+                let value = symbols.eval(expr).unwrap_or_else(|| {
+                    diagnostics.warning(Some(span), format!("cannot evaluate DLGINIT data '{}'", expr));
+                    0
                 });
-                continue;
+                bytes.extend((value as u16).to_le_bytes());
             }
+        }
+    }
 
-            // Example for accelerator tables:
-            let accelerator_table_re = Regex::new(r#"\s*ACCELERATORS\s*BEGIN"#).unwrap();
-            if accelerator_table_re.is_match(line) {
-                let mut accelerator_items = Vec::new();
-                while let Some(Ok(line)) = lines.next() {
-                    let line = line.trim();
-                    if line == "END" {
-                        break;
-                    }
-                    accelerator_items.push(line.to_string()); // Simplified
+    let word = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]) as u32;
+    let mut init = RcDialogInit::default();
+    let mut pos = 0;
+    while pos + 2 <= bytes.len() && word(pos) != 0 {
+        if pos + 8 > bytes.len() {
+            diagnostics.warning(Some(span), "truncated DLGINIT entry");
+            break;
+        }
+        let len = (word(pos + 4) | word(pos + 6) << 16) as usize;
+        let start = pos + 8;
+        let Some(data) = bytes.get(start..start + len) else {
+            diagnostics.warning(Some(span), "DLGINIT entry is longer than the data");
+            break;
+        };
+        init.entries.push(DialogInitEntry {
+            control: word(pos) as i64,
+            message: word(pos + 2),
+            data: data.to_vec(),
+        });
+        pos = start + len;
+    }
+    init
+}
+
+// Class names of the predefined atoms a CONTROL statement may use instead.
+fn predefined_class(atom: i64) -> Option<&'static str> {
+    match atom {
+        0x80 => Some("Button"),
+        0x81 => Some("Edit"),
+        0x82 => Some("Static"),
+        0x83 => Some("ListBox"),
+        0x84 => Some("ScrollBar"),
+        0x85 => Some("ComboBox"),
+        _ => None,
+    }
+}
+
+fn log_version_blocks(blocks: &[VersionBlock], path: &str) {
+    for block in blocks {
+        let path = format!("{}/{}", path, block.name);
+        info!("VERSIONINFO block {} at line {}", path, block.span.line);
+        for value in &block.values {
+            info!("VERSIONINFO {}: {}", path, value);
+        }
+        log_version_blocks(&block.blocks, &path);
+    }
+}
+
+// Reports menu command IDs that do not resolve to a number, and empty popups.
+fn check_menu_ids(items: &[MenuItem], symbols: &SymbolTable, diagnostics: &mut Diagnostics) {
+    for item in items {
+        match item {
+            MenuItem::Separator { .. } => {}
+            MenuItem::Item { args, .. } => {
+                if let Some(id) = args.first() {
+                    symbols.resolve_id(&id.to_string(), item.span(), diagnostics);
+                }
+            }
+            MenuItem::Popup { text, items, .. } => {
+                if items.is_empty() {
+                    diagnostics.warning(Some(item.span()), format!("POPUP \"{}\" has no items", text));
+                }
+                check_menu_ids(items, symbols, diagnostics);
+            }
+        }
+    }
+}
+
+fn join_args(args: &[Expr]) -> String {
+    args.iter().map(Expr::to_string).collect::<Vec<_>>().join(", ")
+}
+
+fn flatten_menu(items: &[MenuItem], depth: usize, table: &mut Vec<String>) {
+    let indent = "    ".repeat(depth);
+    for item in items {
+        match item {
+            MenuItem::Separator { .. } => table.push(format!("{}MENUITEM SEPARATOR", indent)),
+            MenuItem::Item { text, args, .. } => table.push(format!(
+                "{}MENUITEM {}",
+                indent,
+                join_args(&[&[Expr::String(text.clone())], args.as_slice()].concat())
+            )),
+            MenuItem::Popup {
+                text, args, items, ..
+            } => {
+                table.push(format!(
+                    "{}POPUP {}",
+                    indent,
+                    join_args(&[&[Expr::String(text.clone())], args.as_slice()].concat())
+                ));
+                flatten_menu(items, depth + 1, table);
+            }
+        }
+    }
+}
+
+/// Recursive-descent parser turning the token stream into a `Script`.
+pub struct Parser<'d> {
+    tokens: Vec<Token>,
+    pos: usize,
+    // BEGIN/END nesting inside the current resource.
+    depth: usize,
+    diagnostics: &'d mut Diagnostics,
+}
+
+impl<'d> Parser<'d> {
+    /// `tokens` must end with `TokenKind::Eof`, as produced by the lexer.
+    pub fn new(tokens: Vec<Token>, diagnostics: &'d mut Diagnostics) -> Self {
+        Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+            diagnostics,
+        }
+    }
+
+    /// Parses every top-level item. A syntax error is reported and the parser
+    /// resynchronises at the start of the next resource.
+    pub fn script(mut self) -> Script {
+        let mut script = Script::default();
+        while *self.peek() != TokenKind::Eof {
+            self.depth = 0;
+            match self.item() {
+                Ok(item) => script.items.push(item),
+                Err(e) => {
+                    self.diagnostics.error(Some(e.span), e.message);
+                    self.recover();
                 }
-                accelerator_table_list.add(RcAcceleratorTable {
-                    id: String::new(),
-                    table: accelerator_items,
-                });
-                continue;
             }
+        }
+        script
+    }
 
-            // Example for menus:
-            let menu_re = Regex::new(r#"\s*MENU\s*BEGIN"#).unwrap();
-            if menu_re.is_match(line) {
-                let mut menu_items = Vec::new();
-                while let Some(Ok(line)) = lines.next() {
-                    let line = line.trim();
-                    if line == "END" {
-                        break;
+    // Skips to the end of the broken resource, or to the next token that
+    // looks like the start of a new one.
+    fn recover(&mut self) {
+        loop {
+            match self.peek() {
+                TokenKind::Eof => return,
+                TokenKind::Begin => self.depth += 1,
+                TokenKind::End => {
+                    self.bump();
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.depth == 0 {
+                        return;
                     }
-                    menu_items.push(line.to_string()); // Simplified
+                    continue;
                 }
-                menu_list.add(RcMenu {
-                    id: String::new(),
-                    table: menu_items,
+                _ if self.at_resource_start() => return,
+                _ => {}
+            }
+            self.bump();
+        }
+    }
+
+    fn at_resource_start(&self) -> bool {
+        if self.is_keyword("STRINGTABLE") || self.is_keyword("LANGUAGE") {
+            return true;
+        }
+        let next = self.tokens.get(self.pos + 1).map(|t| &t.kind);
+        matches!(self.peek(), TokenKind::Ident(_) | TokenKind::Number(_))
+            && matches!(next, Some(TokenKind::Ident(name))
+                if RESOURCE_TYPES.iter().any(|t| name.eq_ignore_ascii_case(t)))
+    }
+
+    fn peek(&self) -> &TokenKind {
+        &self.tokens[self.pos].kind
+    }
+
+    fn span(&self) -> Span {
+        self.tokens[self.pos].span
+    }
+
+    fn bump(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, ParseError> {
+        Err(ParseError {
+            message,
+            span: self.span(),
+        })
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, ParseError> {
+        let found = match self.peek() {
+            TokenKind::Ident(name) => format!("`{}`", name),
+            TokenKind::Number(n) => format!("number {}", n),
+            TokenKind::String(_) => "string".to_string(),
+            TokenKind::Punct(c) => format!("`{}`", c),
+            TokenKind::Begin => "BEGIN".to_string(),
+            TokenKind::End => "END".to_string(),
+            TokenKind::Eof => "end of file".to_string(),
+        };
+        self.error(format!("expected {}, found {}", expected, found))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), TokenKind::Ident(name) if name.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.bump();
+        }
+        found
+    }
+
+    fn eat_punct(&mut self, c: char) -> bool {
+        let found = *self.peek() == TokenKind::Punct(c);
+        if found {
+            self.bump();
+        }
+        found
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat_punct(c) {
+            Ok(())
+        } else {
+            self.unexpected(&format!("`{}`", c))
+        }
+    }
+
+    fn expect_begin(&mut self) -> Result<(), ParseError> {
+        if *self.peek() == TokenKind::Begin {
+            self.bump();
+            self.depth += 1;
+            Ok(())
+        } else {
+            self.unexpected("BEGIN")
+        }
+    }
+
+    // Consumes END if present; used as the loop condition for blocks.
+    fn at_end(&mut self) -> Result<bool, ParseError> {
+        match self.peek() {
+            TokenKind::End => {
+                self.bump();
+                self.depth -= 1;
+                Ok(true)
+            }
+            TokenKind::Eof => self.unexpected("END"),
+            _ if self.at_resource_start() => {
+                self.error("missing END before the next resource".to_string())
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn keyword(&mut self) -> Result<String, ParseError> {
+        match self.peek() {
+            TokenKind::Ident(name) => {
+                let name = name.to_ascii_uppercase();
+                self.bump();
+                Ok(name)
+            }
+            _ => self.unexpected("a keyword"),
+        }
+    }
+
+    // Adjacent string literals are concatenated, as in C.
+    fn string(&mut self) -> Result<String, ParseError> {
+        let TokenKind::String(first) = self.peek().clone() else {
+            return self.unexpected("a string");
+        };
+        self.bump();
+        let mut text = first;
+        while let TokenKind::String(next) = self.peek().clone() {
+            self.bump();
+            text.push_str(&next);
+        }
+        Ok(text)
+    }
+
+    fn item(&mut self) -> Result<Item, ParseError> {
+        let span = self.span();
+        if self.eat_keyword("LANGUAGE") {
+            let primary = self.expr()?;
+            self.expect_punct(',')?;
+            let sub = self.expr()?;
+            return Ok(Item::Language { primary, sub, span });
+        }
+
+        if self.eat_keyword("STRINGTABLE") {
+            let memory_flags = self.memory_flags();
+            let options = self.options()?;
+            let body = self.string_table()?;
+            return Ok(Item::Resource(ResourceDef {
+                id: None,
+                type_name: "STRINGTABLE".to_string(),
+                memory_flags,
+                options,
+                body,
+                span,
+            }));
+        }
+
+        let id = match self.peek().clone() {
+            TokenKind::Ident(name) => Expr::Symbol(name),
+            TokenKind::Number(n) => Expr::Number(n),
+            TokenKind::String(text) => Expr::String(text),
+            _ => return self.unexpected("a resource ID"),
+        };
+        self.bump();
+
+        let type_name = match self.peek().clone() {
+            TokenKind::Ident(name) => name.to_ascii_uppercase(),
+            TokenKind::Number(n) => standard_type_name(n),
+            TokenKind::String(text) => text,
+            _ => return self.unexpected("a resource type"),
+        };
+        self.bump();
+        let memory_flags = self.memory_flags();
+
+        let mut options = Vec::new();
+        let body = match type_name.as_str() {
+            "DIALOG" | "DIALOGEX" => {
+                let params = self.args()?;
+                options = self.options()?;
+                ResourceBody::Dialog(DialogDef {
+                    params,
+                    controls: self.controls()?,
+                })
+            }
+            "MENU" | "MENUEX" => {
+                options = self.options()?;
+                ResourceBody::Menu(self.menu_items()?)
+            }
+            "ACCELERATORS" => {
+                options = self.options()?;
+                ResourceBody::Accelerators(self.accelerators()?)
+            }
+            "TOOLBAR" => ResourceBody::Toolbar(self.toolbar()?),
+            "VERSIONINFO" => ResourceBody::VersionInfo(self.version_info()?),
+            _ => {
+                options = self.options()?;
+                match self.peek() {
+                    TokenKind::String(_) => ResourceBody::File(self.string()?),
+                    TokenKind::Begin => ResourceBody::Data(self.data()?),
+                    _ => return self.unexpected("a file name or BEGIN"),
+                }
+            }
+        };
+
+        Ok(Item::Resource(ResourceDef {
+            id: Some(id),
+            type_name,
+            memory_flags,
+            options,
+            body,
+            span,
+        }))
+    }
+
+    fn memory_flags(&mut self) -> Vec<String> {
+        let mut flags = Vec::new();
+        while let TokenKind::Ident(name) = self.peek() {
+            let name = name.to_ascii_uppercase();
+            if !MEMORY_FLAGS.contains(&name.as_str()) {
+                break;
+            }
+            self.bump();
+            flags.push(name);
+        }
+        flags
+    }
+
+    // Optional statements between a resource header and BEGIN.
+    fn options(&mut self) -> Result<Vec<Statement>, ParseError> {
+        let mut options = Vec::new();
+        while let TokenKind::Ident(_) = self.peek() {
+            let span = self.span();
+            let keyword = self.keyword()?;
+            options.push(Statement {
+                keyword,
+                args: self.args()?,
+                span,
+            });
+        }
+        Ok(options)
+    }
+
+    fn starts_expr(&self) -> bool {
+        matches!(
+            self.peek(),
+            TokenKind::Ident(_)
+                | TokenKind::Number(_)
+                | TokenKind::String(_)
+                | TokenKind::Punct('-')
+                | TokenKind::Punct('~')
+                | TokenKind::Punct('(')
+        )
+    }
+
+    // A comma-separated argument list, possibly empty. The list ends at the
+    // first token that neither continues an expression nor is a comma.
+    fn args(&mut self) -> Result<Vec<Expr>, ParseError> {
+        let mut args = Vec::new();
+        if !self.starts_expr() {
+            return Ok(args);
+        }
+        args.push(self.expr()?);
+        while self.eat_punct(',') {
+            args.push(self.expr()?);
+        }
+        Ok(args)
+    }
+
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.and_expr()?;
+        while self.eat_punct('|') {
+            lhs = Expr::Binary(BinaryOp::Or, Box::new(lhs), Box::new(self.and_expr()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.additive()?;
+        while self.eat_punct('&') {
+            lhs = Expr::Binary(BinaryOp::And, Box::new(lhs), Box::new(self.additive()?));
+        }
+        Ok(lhs)
+    }
+
+    fn additive(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                TokenKind::Punct('+') => BinaryOp::Add,
+                TokenKind::Punct('-') => BinaryOp::Sub,
+                _ => return Ok(lhs),
+            };
+            self.bump();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.multiplicative()?));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                TokenKind::Punct('*') => BinaryOp::Mul,
+                TokenKind::Punct('/') => BinaryOp::Div,
+                _ => return Ok(lhs),
+            };
+            self.bump();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        let op = if self.eat_punct('-') {
+            UnaryOp::Neg
+        } else if self.eat_punct('~') {
+            UnaryOp::BitNot
+        } else if self.eat_keyword("NOT") {
+            UnaryOp::Not
+        } else {
+            return self.primary();
+        };
+        Ok(Expr::Unary(op, Box::new(self.unary()?)))
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        match self.peek().clone() {
+            TokenKind::Number(n) => {
+                self.bump();
+                Ok(Expr::Number(n))
+            }
+            TokenKind::Ident(name) => {
+                self.bump();
+                Ok(Expr::Symbol(name))
+            }
+            TokenKind::String(_) => Ok(Expr::String(self.string()?)),
+            TokenKind::Punct('(') => {
+                self.bump();
+                let expr = self.expr()?;
+                self.expect_punct(')')?;
+                Ok(expr)
+            }
+            _ => self.unexpected("an expression"),
+        }
+    }
+
+    fn controls(&mut self) -> Result<Vec<Statement>, ParseError> {
+        self.expect_begin()?;
+        let mut controls = Vec::new();
+        while !self.at_end()? {
+            let span = self.span();
+            let keyword = self.keyword()?;
+            let args = self.args()?;
+            // DIALOGEX controls may carry creation data, which Qt has no use for.
+            if *self.peek() == TokenKind::Begin {
+                self.data()?;
+            }
+            controls.push(Statement {
+                keyword,
+                args,
+                span,
+            });
+        }
+        Ok(controls)
+    }
+
+    fn string_table(&mut self) -> Result<ResourceBody, ParseError> {
+        self.expect_begin()?;
+        let mut entries = Vec::new();
+        while !self.at_end()? {
+            let span = self.span();
+            let id = self.expr()?;
+            self.eat_punct(',');
+            entries.push(StringEntry {
+                id,
+                text: self.string()?,
+                span,
+            });
+        }
+        Ok(ResourceBody::StringTable(entries))
+    }
+
+    fn accelerators(&mut self) -> Result<Vec<Accelerator>, ParseError> {
+        self.expect_begin()?;
+        let mut entries = Vec::new();
+        while !self.at_end()? {
+            if !self.starts_expr() {
+                return self.unexpected("an accelerator key");
+            }
+            let span = self.span();
+            entries.push(Accelerator {
+                args: self.args()?,
+                span,
+            });
+        }
+        Ok(entries)
+    }
+
+    fn menu_items(&mut self) -> Result<Vec<MenuItem>, ParseError> {
+        self.expect_begin()?;
+        let mut items = Vec::new();
+        while !self.at_end()? {
+            let span = self.span();
+            if self.eat_keyword("MENUITEM") {
+                if self.eat_keyword("SEPARATOR") {
+                    items.push(MenuItem::Separator { span });
+                    continue;
+                }
+                let text = self.string()?;
+                let args = self.menu_args()?;
+                items.push(MenuItem::Item { text, args, span });
+            } else if self.eat_keyword("POPUP") {
+                let text = self.string()?;
+                let args = self.menu_args()?;
+                let children = self.menu_items()?;
+                items.push(MenuItem::Popup {
+                    text,
+                    args,
+                    items: children,
+                    span,
                 });
+            } else {
+                return self.unexpected("MENUITEM or POPUP");
+            }
+        }
+        Ok(items)
+    }
+
+    // Menu options like CHECKED and GRAYED may follow without commas.
+    fn menu_args(&mut self) -> Result<Vec<Expr>, ParseError> {
+        let mut args = Vec::new();
+        while self.eat_punct(',') {
+            if *self.peek() == TokenKind::Punct(',') {
+                // MENUEX allows empty arguments, e.g. `MENUITEM "x",,,`.
+                args.push(Expr::Number(0));
                 continue;
             }
+            args.push(self.expr()?);
+        }
+        while let TokenKind::Ident(name) = self.peek() {
+            if !MENU_OPTIONS.iter().any(|o| name.eq_ignore_ascii_case(o)) {
+                break;
+            }
+            args.push(Expr::Symbol(name.to_ascii_uppercase()));
+            self.bump();
+            self.eat_punct(',');
+        }
+        Ok(args)
+    }
 
-            // Example for dialogs:
-            let dialog_re = Regex::new(r#"(\w+)\s+DIALOGEX"#).unwrap();
-            if let Some(caps) = dialog_re.captures(line) {
-                let dialog_id = caps[1].to_string();
-                let mut dialog_items = Vec::new();
-                while let Some(Ok(line)) = lines.next() {
-                    let line = line.trim();
-                    if line == "END" {
-                        break;
-                    }
-                    dialog_items.push(line.to_string()); // Simplified
-                }
-                dialog_ex_list.add(RcDialogEx {
-                    id: dialog_id,
-                    items: dialog_items,
-                    options: Vec::new(),
-                    numbers: Vec::new(),
-                    caption: String::new(),
-                    style: String::new(),
-                    font: String::new(),
+    fn toolbar(&mut self) -> Result<ToolbarDef, ParseError> {
+        let width = self.expr()?;
+        self.expect_punct(',')?;
+        let height = self.expr()?;
+        self.expect_begin()?;
+        let mut buttons = Vec::new();
+        while !self.at_end()? {
+            if self.eat_keyword("SEPARATOR") {
+                buttons.push(None);
+            } else if self.eat_keyword("BUTTON") {
+                buttons.push(Some(self.expr()?));
+            } else {
+                return self.unexpected("BUTTON or SEPARATOR");
+            }
+        }
+        Ok(ToolbarDef {
+            width,
+            height,
+            buttons,
+        })
+    }
+
+    fn version_info(&mut self) -> Result<VersionInfoDef, ParseError> {
+        let fixed = self.options()?;
+        self.expect_begin()?;
+        let mut blocks = Vec::new();
+        while !self.at_end()? {
+            blocks.push(self.version_block()?);
+        }
+        Ok(VersionInfoDef { fixed, blocks })
+    }
+
+    fn version_block(&mut self) -> Result<VersionBlock, ParseError> {
+        let span = self.span();
+        if !self.eat_keyword("BLOCK") {
+            return self.unexpected("BLOCK");
+        }
+        let name = self.string()?;
+        self.expect_begin()?;
+        let mut block = VersionBlock {
+            name,
+            values: Vec::new(),
+            blocks: Vec::new(),
+            span,
+        };
+        while !self.at_end()? {
+            let span = self.span();
+            if self.is_keyword("BLOCK") {
+                block.blocks.push(self.version_block()?);
+            } else if self.eat_keyword("VALUE") {
+                block.values.push(Statement {
+                    keyword: "VALUE".to_string(),
+                    args: self.args()?,
+                    span,
                 });
+            } else {
+                return self.unexpected("BLOCK or VALUE");
             }
         }
+        Ok(block)
+    }
+
+    // Raw data of RCDATA, DLGINIT and user-defined resources; commas are optional.
+    fn data(&mut self) -> Result<Vec<Expr>, ParseError> {
+        self.expect_begin()?;
+        let mut data = Vec::new();
+        while !self.at_end()? {
+            data.push(self.expr()?);
+            self.eat_punct(',');
+        }
+        Ok(data)
     }
+}
 
-    (
-        bitmap_list, icon_list, string_table_list, toolbar_list, accelerator_table_list, menu_list, dialog_ex_list
-    )
+// Names of the predefined RT_* resource types, for scripts that use numbers.
+fn standard_type_name(n: i64) -> String {
+    match n {
+        1 => "CURSOR",
+        2 => "BITMAP",
+        3 => "ICON",
+        4 => "MENU",
+        5 => "DIALOG",
+        6 => "STRINGTABLE",
+        8 => "FONT",
+        9 => "ACCELERATORS",
+        10 => "RCDATA",
+        11 => "MESSAGETABLE",
+        16 => "VERSIONINFO",
+        23 => "HTML",
+        24 => "MANIFEST",
+        _ => return n.to_string(),
+    }
+    .to_string()
 }
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<std::fs::File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = std::fs::File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preprocessor::{PreprocessOptions, Preprocessor};
+    use crate::styles::*;
+
+    fn parse(text: &str, diagnostics: &mut Diagnostics) -> ResourceScript {
+        let source = Preprocessor::new(&PreprocessOptions::default(), diagnostics).process_str("test.rc", text);
+        parse_source(&source, diagnostics)
+    }
+
+    #[test]
+    fn recovers_at_the_end_of_a_broken_resource() {
+        let mut diagnostics = Diagnostics::new();
+        let script = parse(
+            "1 DIALOG 0, 0, 100, 50\nBEGIN\n  PUSHBUTTON \"OK\", , ,\n  BEGIN END\nEND\n\
+             2 MENU\nBEGIN\n  MENUITEM \"Open\", 10\nEND\n",
+            &mut diagnostics,
+        );
+        assert!(diagnostics.has_errors());
+        assert!(script.get("MENU", "2").is_some());
+    }
+
+    #[test]
+    fn recovers_at_the_next_resource() {
+        let mut diagnostics = Diagnostics::new();
+        let script = parse(
+            "1 MENU\nBEGIN\n  MENUITEM 5\n2 MENU\nBEGIN\n  MENUITEM \"Open\", 10\nEND\n",
+            &mut diagnostics,
+        );
+        assert!(diagnostics.has_errors());
+        assert!(script.get("MENU", "2").is_some());
+    }
+
+    #[test]
+    fn dialog_init_entries() {
+        let mut diagnostics = Diagnostics::new();
+        let script = parse(
+            "#define IDC_COMBO 1001\n\
+             100 DLGINIT\nBEGIN\n\
+             IDC_COMBO, 0x403, 3, 0\n\"ab\\000\"\n\
+             IDC_COMBO, 0x403, 2, 0\n\"cd\"\n\
+             0\nEND\n",
+            &mut diagnostics,
+        );
+        assert!(!diagnostics.has_errors());
+        let init = script
+            .dialog_init(&RcId {
+                name: "100".into(),
+                value: Some(100),
+            })
+            .unwrap();
+        let texts: Vec<String> = init.for_control(1001).map(DialogInitEntry::text).collect();
+        assert_eq!(texts, ["ab", "cd"]);
+        assert!(init.entries.iter().all(|entry| entry.message == 0x403));
+    }
+
+    #[test]
+    fn truncated_dialog_init_is_a_warning() {
+        let mut diagnostics = Diagnostics::new();
+        let data = [Expr::Number(1001), Expr::Number(0x403), Expr::Number(8), Expr::Number(0)];
+        let init = dialog_init(&data, Span::default(), &SymbolTable::default(), &mut diagnostics);
+        assert!(init.entries.is_empty());
+        assert_eq!(diagnostics.count(crate::diagnostics::Severity::Warning), 1);
+    }
+
+    fn parse_ok(text: &str) -> ResourceScript {
+        let mut diagnostics = Diagnostics::new();
+        let script = parse(text, &mut diagnostics);
+        assert!(!diagnostics.has_errors(), "{}", diagnostics.render());
+        script
+    }
+
+    #[test]
+    fn dialogex_header() {
+        let script = parse_ok(
+            "IDD_MAIN DIALOGEX 10, 20, 200, 100, 55\n\
+             STYLE DS_SETFONT | WS_POPUP | WS_CAPTION | WS_SYSMENU\n\
+             EXSTYLE WS_EX_TOOLWINDOW\n\
+             CAPTION \"Settings\"\n\
+             FONT 9, \"Segoe UI\", 700, 1, 0x1\n\
+             MENU IDR_MAIN\n\
+             CLASS \"MyDialogClass\"\n\
+             BEGIN\nEND\n",
+        );
+        let (_, dialog) = script.dialogs().next().unwrap();
+        assert!(dialog.extended);
+        assert_eq!((dialog.x, dialog.y, dialog.width, dialog.height), (10, 20, 200, 100));
+        assert_eq!(dialog.help_id, Some(55));
+        assert_eq!(dialog.caption, "Settings");
+        assert_eq!(dialog.effective_style, DS_SETFONT | WS_POPUP | WS_CAPTION | WS_SYSMENU);
+        assert_eq!(dialog.effective_ex_style, WS_EX_TOOLWINDOW);
+        let font = dialog.font.as_ref().unwrap();
+        assert_eq!((font.point_size, font.face.as_str()), (9, "Segoe UI"));
+        assert_eq!((font.weight, font.italic, font.charset), (700, true, 1));
+        assert_eq!(dialog.menu.as_ref().unwrap().name, "IDR_MAIN");
+        assert_eq!(dialog.class.as_deref(), Some("MyDialogClass"));
+    }
+
+    #[test]
+    fn dialog_defaults_without_style() {
+        let script = parse_ok("1 DIALOG 0, 0, 100, 50\nCAPTION \"Plain\"\nFONT 8, \"MS Shell Dlg\"\nBEGIN\nEND\n");
+        let (_, dialog) = script.dialogs().next().unwrap();
+        assert!(!dialog.extended);
+        assert_eq!(dialog.help_id, None);
+        assert_eq!(dialog.effective_style, WS_POPUP | WS_BORDER | WS_SYSMENU | WS_CAPTION | DS_SETFONT);
+        let font = dialog.font.as_ref().unwrap();
+        assert_eq!((font.weight, font.italic), (0, false));
+    }
+
+    #[test]
+    fn control_statements() {
+        let script = parse_ok(
+            "#define IDC_NAME 1001\n\
+             1 DIALOGEX 0, 0, 200, 100\nBEGIN\n\
+             LTEXT \"&Name:\", -1, 5, 5, 40, 8\n\
+             EDITTEXT IDC_NAME, 50, 5, 100, 12, ES_AUTOHSCROLL | NOT WS_BORDER, WS_EX_CLIENTEDGE, 77\n\
+             CONTROL \"Check\", 1002, \"Button\", BS_AUTOCHECKBOX | WS_TABSTOP, 5, 20, 60, 10\n\
+             CONTROL \"\", 1003, 0x85, CBS_DROPDOWNLIST, 5, 35, 60, 50\n\
+             ICON IDI_APP, -1, 5, 50\n\
+             DEFPUSHBUTTON \"OK\", IDOK, 150, 80, 45, 14, NOT WS_TABSTOP\n\
+             END\n",
+        );
+        let (_, dialog) = script.dialogs().next().unwrap();
+        let [label, edit, check, combo, icon, ok] = dialog.controls.as_slice() else {
+            panic!("expected six controls, got {:?}", dialog.controls);
+        };
+
+        assert_eq!((label.kind.as_str(), label.class.as_str(), label.text.as_str()), ("LTEXT", "Static", "&Name:"));
+        assert_eq!(label.id.value, Some(-1));
+        assert_eq!(label.effective_style, SS_LEFT | WS_GROUP | WS_CHILD | WS_VISIBLE);
+
+        assert_eq!((edit.class.as_str(), edit.id.name.as_str(), edit.id.value), ("Edit", "IDC_NAME", Some(1001)));
+        assert_eq!((edit.x, edit.y, edit.width, edit.height), (50, 5, 100, 12));
+        assert_eq!(edit.effective_style, ES_AUTOHSCROLL | WS_TABSTOP | WS_CHILD | WS_VISIBLE);
+        assert_eq!(edit.effective_ex_style, WS_EX_CLIENTEDGE);
+        assert_eq!(edit.help_id, Some(77));
+
+        assert_eq!(check.class, "Button");
+        assert_eq!(check.effective_style, BS_AUTOCHECKBOX | WS_TABSTOP | WS_CHILD | WS_VISIBLE);
+        assert_eq!(combo.class, "ComboBox");
+        assert_eq!(combo.effective_style & CBS_DROPDOWNLIST, CBS_DROPDOWNLIST);
+
+        assert_eq!((icon.text.as_str(), icon.width, icon.height), ("IDI_APP", 0, 0));
+        assert_eq!(icon.effective_style & SS_TYPEMASK, SS_ICON);
+
+        assert_eq!(ok.id.value, Some(1));
+        assert_eq!(ok.effective_style, BS_DEFPUSHBUTTON | WS_CHILD | WS_VISIBLE);
+    }
+
+    #[test]
+    fn incomplete_controls_are_errors() {
+        let mut diagnostics = Diagnostics::new();
+        parse(
+            "1 DIALOG 0, 0, 100, 50\nBEGIN\n  PUSHBUTTON \"OK\", IDOK, 5, 5\n  CONTROL \"x\", 5\nEND\n",
+            &mut diagnostics,
+        );
+        let rendered = diagnostics.render();
+        assert!(rendered.contains("PUSHBUTTON needs x, y, width and height"));
+        assert!(rendered.contains("CONTROL needs a window class"));
+    }
 }
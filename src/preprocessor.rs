@@ -0,0 +1,929 @@
+use crate::diagnostics::{Diagnostics, Span};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Guards against `#include` cycles.
+const MAX_INCLUDE_DEPTH: usize = 64;
+
+/// Settings for the preprocessing stage, mirroring rc.exe `/i` and `/d`.
+#[derive(Debug, Clone, Default)]
+pub struct PreprocessOptions {
+    pub include_paths: Vec<PathBuf>,
+    pub defines: Vec<(String, String)>,
+}
+
+/// A `#define`d macro. `params` is `Some` for function-like macros.
+#[derive(Debug, Clone)]
+pub struct Macro {
+    pub params: Option<Vec<String>>,
+    pub body: String,
+}
+
+/// Origin of one line of preprocessed output: index into the `SourceMap` and
+/// 1-based line number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLine {
+    pub file: usize,
+    pub line: usize,
+}
+
+/// The active text of a script after preprocessing. `lines[n]` tells where the
+/// n-th line of `text` came from.
+#[derive(Debug, Default)]
+pub struct PreprocessedSource {
+    pub text: String,
+    pub lines: Vec<SourceLine>,
+    pub macros: HashMap<String, Macro>,
+    pub code_page: Option<u32>,
+}
+
+struct Conditional {
+    span: Span,
+    parent_active: bool,
+    taken: bool,
+    active: bool,
+    seen_else: bool,
+}
+
+pub struct Preprocessor<'d> {
+    include_paths: Vec<PathBuf>,
+    macros: HashMap<String, Macro>,
+    diagnostics: &'d mut Diagnostics,
+    text: String,
+    lines: Vec<SourceLine>,
+    conditionals: Vec<Conditional>,
+    code_page: Option<u32>,
+    depth: usize,
+}
+
+impl<'d> Preprocessor<'d> {
+    /// Problems found while preprocessing, and the text of every file read,
+    /// are recorded in `diagnostics`.
+    pub fn new(options: &PreprocessOptions, diagnostics: &'d mut Diagnostics) -> Self {
+        let mut preprocessor = Preprocessor {
+            include_paths: options.include_paths.clone(),
+            macros: HashMap::new(),
+            diagnostics,
+            text: String::new(),
+            lines: Vec::new(),
+            conditionals: Vec::new(),
+            code_page: None,
+            depth: 0,
+        };
+        preprocessor.define("RC_INVOKED", "1");
+        preprocessor.define("_WIN32", "1");
+        for (name, value) in &options.defines {
+            preprocessor.define(name, value);
+        }
+        preprocessor
+    }
+
+    fn define(&mut self, name: &str, body: &str) {
+        self.macros.insert(
+            name.to_string(),
+            Macro {
+                params: None,
+                body: body.to_string(),
+            },
+        );
+    }
+
+    pub fn process_file<P: AsRef<Path>>(mut self, path: P) -> io::Result<PreprocessedSource> {
+        self.include_file(path.as_ref())?;
+        Ok(self.finish())
+    }
+
+    /// Preprocesses a script that is already in memory, e.g. read from a pipe.
+    /// `name` is shown in diagnostics and quoted includes are searched next to it.
+    pub fn process_bytes<P: AsRef<Path>>(mut self, name: P, bytes: &[u8]) -> PreprocessedSource {
+        self.include_bytes(name.as_ref(), bytes);
+        self.finish()
+    }
+
+    /// Like `process_bytes`, for text that is known to be Unicode already.
+    pub fn process_str<P: AsRef<Path>>(mut self, name: P, text: &str) -> PreprocessedSource {
+        let file = self.diagnostics.sources.add_file(name.as_ref());
+        let lines: Vec<&[u8]> = text.as_bytes().split(|&b| b == b'\n').collect();
+        self.process_lines(file, &lines, true, false);
+        self.finish()
+    }
+
+    fn finish(self) -> PreprocessedSource {
+        for conditional in &self.conditionals {
+            self.diagnostics
+                .error(Some(conditional.span), "unterminated conditional directive");
+        }
+        PreprocessedSource {
+            text: self.text,
+            lines: self.lines,
+            macros: self.macros,
+            code_page: self.code_page,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.conditionals.last().is_none_or(|c| c.active)
+    }
+
+    fn include_file(&mut self, path: &Path) -> io::Result<()> {
+        let bytes = fs::read(path)?;
+        self.include_bytes(path, &bytes);
+        Ok(())
+    }
+
+    fn include_bytes(&mut self, path: &Path, bytes: &[u8]) {
+        let file = self.diagnostics.sources.add_file(path);
+
+        // rc.exe only honours preprocessor directives in C headers and sources.
+        let directives_only = matches!(
+            path.extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_ascii_lowercase())
+                .as_deref(),
+            Some("h") | Some("hpp") | Some("c")
+        );
+
+        if bytes.starts_with(&[0xFF, 0xFE]) {
+            let units: Vec<u16> = bytes[2..]
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            let decoded = String::from_utf16_lossy(&units);
+            let lines: Vec<&[u8]> = decoded.as_bytes().split(|&b| b == b'\n').collect();
+            self.process_lines(file, &lines, true, directives_only);
+        } else if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+            let lines: Vec<&[u8]> = rest.split(|&b| b == b'\n').collect();
+            self.process_lines(file, &lines, true, directives_only);
+        } else {
+            let lines: Vec<&[u8]> = bytes.split(|&b| b == b'\n').collect();
+            self.process_lines(file, &lines, false, directives_only);
+        }
+    }
+
+    fn decode(&self, bytes: &[u8], utf8: bool) -> String {
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        if utf8 {
+            return String::from_utf8_lossy(bytes).into_owned();
+        }
+        match self.code_page {
+            Some(1252) => decode_windows_1252(bytes),
+            Some(65001) => String::from_utf8_lossy(bytes).into_owned(),
+            _ => match std::str::from_utf8(bytes) {
+                Ok(text) => text.to_string(),
+                // Legacy scripts without a pragma are almost always ANSI Western.
+                Err(_) => decode_windows_1252(bytes),
+            },
+        }
+    }
+
+    fn process_lines(
+        &mut self,
+        file: usize,
+        raw: &[&[u8]],
+        utf8: bool,
+        directives_only: bool,
+    ) {
+        let mut in_comment = false;
+        // Set while a comment opened on a directive line is open. The lexer
+        // never sees its `/*`, so the comment is stripped here.
+        let mut directive_comment = false;
+        let mut i = 0;
+        while i < raw.len() {
+            let line_no = i + 1;
+            let mut line = self.decode(raw[i], utf8);
+            self.diagnostics.sources.push_line(file, &line);
+            i += 1;
+
+            if !in_comment && line.trim_start().starts_with('#') {
+                let indent = line.len() - line.trim_start().len();
+                let span = Span {
+                    file,
+                    line: line_no,
+                    column: line[..indent].chars().count() + 1,
+                    len: line.trim().chars().count(),
+                };
+                while line.ends_with('\\') && i < raw.len() {
+                    let next = self.decode(raw[i], utf8);
+                    self.diagnostics.sources.push_line(file, &next);
+                    line.pop();
+                    line.push_str(&next);
+                    i += 1;
+                }
+                let directive = strip_comments(&line, &mut in_comment);
+                directive_comment = in_comment;
+                let directive = directive.trim_start()[1..].trim();
+                self.directive(directive, span);
+            } else {
+                let starts_in_comment = in_comment && !directive_comment;
+                if directive_comment {
+                    line = strip_comments(&line, &mut in_comment);
+                    directive_comment = in_comment;
+                } else {
+                    in_comment = skip_comments(&line, in_comment);
+                }
+                if self.is_active() && !directives_only {
+                    self.text
+                        .push_str(&expand_text(&line, starts_in_comment, &self.macros, &mut Vec::new()));
+                    self.text.push('\n');
+                    self.lines.push(SourceLine { file, line: line_no });
+                }
+            }
+        }
+    }
+
+    fn directive(&mut self, directive: &str, span: Span) {
+        let (name, rest) = match directive.find(|c: char| !c.is_ascii_alphanumeric() && c != '_') {
+            Some(pos) => (&directive[..pos], directive[pos..].trim()),
+            None => (directive, ""),
+        };
+
+        match name {
+            "if" | "ifdef" | "ifndef" => {
+                let parent_active = self.is_active();
+                let active = parent_active
+                    && match name {
+                        "ifdef" => self.macros.contains_key(first_word(rest)),
+                        "ifndef" => !self.macros.contains_key(first_word(rest)),
+                        _ => self.condition(rest, span),
+                    };
+                self.conditionals.push(Conditional {
+                    span,
+                    parent_active,
+                    taken: active,
+                    active,
+                    seen_else: false,
+                });
+            }
+            "elif" => {
+                let evaluate = match self.conditionals.last() {
+                    Some(c) if !c.seen_else => c.parent_active && !c.taken,
+                    _ => {
+                        self.diagnostics.error(Some(span), "#elif without #if");
+                        return;
+                    }
+                };
+                let active = evaluate && self.condition(rest, span);
+                if let Some(c) = self.conditionals.last_mut() {
+                    c.active = active;
+                    c.taken |= active;
+                }
+            }
+            "else" => match self.conditionals.last_mut() {
+                Some(c) if !c.seen_else => {
+                    c.active = c.parent_active && !c.taken;
+                    c.taken = true;
+                    c.seen_else = true;
+                }
+                _ => self.diagnostics.error(Some(span), "#else without #if"),
+            },
+            "endif" => {
+                if self.conditionals.pop().is_none() {
+                    self.diagnostics.error(Some(span), "#endif without #if");
+                }
+            }
+            _ if !self.is_active() => {}
+            "include" => self.include(rest, span),
+            "define" => self.define_directive(rest, span),
+            "undef" => {
+                self.macros.remove(first_word(rest));
+            }
+            "pragma" => self.pragma(rest, span),
+            "error" => self.diagnostics.error(Some(span), format!("#error {}", rest)),
+            "line" | "" => {}
+            _ => self.diagnostics.warning(
+                Some(span),
+                format!("unknown preprocessor directive #{}", name),
+            ),
+        }
+    }
+
+    fn condition(&mut self, expr: &str, span: Span) -> bool {
+        match evaluate(expr, &self.macros) {
+            Ok(value) => value != 0,
+            Err(message) => {
+                self.diagnostics
+                    .error(Some(span), format!("invalid #if expression: {}", message));
+                false
+            }
+        }
+    }
+
+    fn define_directive(&mut self, rest: &str, span: Span) {
+        let name_end = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        let name = &rest[..name_end];
+        if name.is_empty() {
+            self.diagnostics.error(Some(span), "#define without a macro name");
+            return;
+        }
+        let after = &rest[name_end..];
+        let (params, body) = match after.strip_prefix('(') {
+            Some(list) => match list.find(')') {
+                Some(close) => (
+                    Some(
+                        list[..close]
+                            .split(',')
+                            .map(|p| p.trim().to_string())
+                            .filter(|p| !p.is_empty())
+                            .collect(),
+                    ),
+                    list[close + 1..].trim(),
+                ),
+                None => {
+                    self.diagnostics
+                        .error(Some(span), "unterminated macro parameter list");
+                    return;
+                }
+            },
+            None => (None, after.trim()),
+        };
+        self.macros.insert(
+            name.to_string(),
+            Macro {
+                params,
+                body: body.to_string(),
+            },
+        );
+    }
+
+    fn include(&mut self, rest: &str, span: Span) {
+        let (name, quoted) = if let Some(inner) = rest.strip_prefix('"') {
+            (inner.split('"').next().unwrap_or(""), true)
+        } else if let Some(inner) = rest.strip_prefix('<') {
+            (inner.split('>').next().unwrap_or(""), false)
+        } else {
+            self.diagnostics
+                .error(Some(span), format!("malformed #include {}", rest));
+            return;
+        };
+
+        // Scripts written on Windows use backslashes in include names, often
+        // doubled as in a C string.
+        let name = name.replace("\\\\", "/").replace('\\', "/");
+        let mut candidates = Vec::new();
+        if quoted {
+            if let Some(dir) = self.diagnostics.sources.path(span.file).parent() {
+                candidates.push(dir.join(&name));
+            }
+        }
+        candidates.extend(self.include_paths.iter().map(|dir| dir.join(&name)));
+
+        let Some(found) = candidates.into_iter().find(|c| c.is_file()) else {
+            // System headers such as afxres.h are usually unavailable off Windows.
+            self.diagnostics.warning(
+                Some(span),
+                format!("cannot find include file '{}', skipping", name),
+            );
+            return;
+        };
+
+        if self.depth >= MAX_INCLUDE_DEPTH {
+            self.diagnostics.error(Some(span), "#include nested too deeply");
+            return;
+        }
+        self.depth += 1;
+        if let Err(e) = self.include_file(&found) {
+            self.diagnostics.error(
+                Some(span),
+                format!("cannot read '{}': {}", found.display(), e),
+            );
+        }
+        self.depth -= 1;
+    }
+
+    fn pragma(&mut self, rest: &str, span: Span) {
+        if let Some(arg) = rest
+            .strip_prefix("code_page")
+            .map(|a| a.trim().trim_start_matches('(').trim_end_matches(')').trim())
+        {
+            self.code_page = if arg.eq_ignore_ascii_case("DEFAULT") {
+                None
+            } else {
+                arg.parse().ok()
+            };
+            if let Some(cp) = self.code_page {
+                if cp != 1252 && cp != 65001 {
+                    self.diagnostics.warning(
+                        Some(span),
+                        format!("code page {} is not supported, decoding as UTF-8", cp),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn first_word(text: &str) -> &str {
+    text.split_whitespace().next().unwrap_or("")
+}
+
+// Removes comments from a directive line, noting whether a block comment stays open.
+fn strip_comments(line: &str, in_comment: &mut bool) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if *in_comment {
+            if c == '*' && chars.peek() == Some(&'/') {
+                chars.next();
+                *in_comment = false;
+                out.push(' ');
+            }
+            continue;
+        }
+        if in_string {
+            out.push(c);
+            if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('/', Some('/')) => break,
+            ('/', Some('*')) => {
+                chars.next();
+                *in_comment = true;
+            }
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// Tracks block comments across ordinary lines so `#` inside them is not a directive.
+fn skip_comments(line: &str, in_comment: bool) -> bool {
+    let mut in_comment = in_comment;
+    strip_comments(line, &mut in_comment);
+    in_comment
+}
+
+// Expands the object-like macros of a line of script text that do not stand
+// for an integer, such as `#define APP_NAME "My App"`. Integer macros stay
+// symbolic, so that resources keep the names of their IDs; the parser gets
+// their values from the symbol table. Strings and comments are left alone.
+fn expand_text(line: &str, in_comment: bool, macros: &HashMap<String, Macro>, hidden: &mut Vec<String>) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_comment = in_comment;
+    let mut in_string = false;
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if in_comment {
+            out.push(c);
+            if c == '*' && chars.next_if(|&(_, next)| next == '/').is_some() {
+                out.push('/');
+                in_comment = false;
+            }
+            continue;
+        }
+        if in_string {
+            out.push(c);
+            in_string = c != '"';
+            continue;
+        }
+        match (c, chars.peek().map(|&(_, next)| next)) {
+            ('/', Some('/')) => {
+                out.push_str(&line[start..]);
+                break;
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                out.push_str("/*");
+                in_comment = true;
+            }
+            ('"', _) => {
+                out.push(c);
+                in_string = true;
+            }
+            _ if c.is_ascii_alphanumeric() || c == '_' => {
+                let mut end = start + 1;
+                while let Some((i, _)) = chars.next_if(|&(_, next)| next.is_ascii_alphanumeric() || next == '_') {
+                    end = i + 1;
+                }
+                let word = &line[start..end];
+                let expansion = macros.get(word).filter(|m| {
+                    let integer = tokenize(&m.body).and_then(|body| expand(&body, macros, &mut Vec::new()));
+                    !c.is_ascii_digit() && m.params.is_none() && integer.is_err() && !hidden.iter().any(|h| h == word)
+                });
+                match expansion {
+                    Some(m) => {
+                        hidden.push(word.to_string());
+                        out.push_str(&expand_text(&m.body, false, macros, hidden));
+                        hidden.pop();
+                    }
+                    None => out.push_str(word),
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn decode_windows_1252(bytes: &[u8]) -> String {
+    const HIGH: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž',
+        '\u{8F}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}',
+        'ž', 'Ÿ',
+    ];
+    bytes
+        .iter()
+        .map(|&b| match b {
+            0x80..=0x9F => HIGH[(b - 0x80) as usize],
+            _ => b as char,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(i64),
+    Ident(String),
+    Op(&'static str),
+}
+
+const OPERATORS: [&str; 27] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<<", ">>", "+", "-", "*", "/", "%", "<", ">", "!", "~",
+    "&", "|", "^", "?", ":", "(", ")", ",", "#", "=",
+];
+
+/// Evaluates a C preprocessor integer expression, expanding `macros` and `defined`.
+/// Identifiers that are not macros evaluate to 0, as in C.
+pub fn evaluate(expr: &str, macros: &HashMap<String, Macro>) -> Result<i64, String> {
+    evaluate_with(expr, macros, false)
+}
+
+/// Like `evaluate`, but fails on identifiers that do not expand to a number.
+/// Used to compute the value of `#define`d symbols.
+pub fn evaluate_strict(expr: &str, macros: &HashMap<String, Macro>) -> Result<i64, String> {
+    evaluate_with(expr, macros, true)
+}
+
+fn evaluate_with(expr: &str, macros: &HashMap<String, Macro>, strict: bool) -> Result<i64, String> {
+    let tokens = tokenize(expr)?;
+    let tokens = expand(&tokens, macros, &mut Vec::new())?;
+    let mut parser = ExprParser {
+        tokens: &tokens,
+        pos: 0,
+        strict,
+        evaluating: true,
+    };
+    let value = parser.conditional()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(value),
+        Some(token) => Err(format!("unexpected {:?}", token)),
+    }
+}
+
+/// Parses a C integer literal: decimal, `0x` hex, octal, char literal, with `U`/`L` suffixes.
+pub fn parse_number(text: &str) -> Option<i64> {
+    if let Some(inner) = text.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')) {
+        let mut chars = inner.chars();
+        return match (chars.next(), chars.next()) {
+            (Some('\\'), Some(c)) => Some(match c {
+                'n' => 10,
+                't' => 9,
+                'r' => 13,
+                '0' => 0,
+                c => c as i64,
+            }),
+            (Some(c), None) => Some(c as i64),
+            _ => None,
+        };
+    }
+    let digits = text.trim_end_matches(['u', 'U', 'l', 'L']);
+    if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        i64::from_str_radix(&digits[1..], 8).ok()
+    } else {
+        digits.parse().ok()
+    }
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = expr.trim_start();
+    while !rest.is_empty() {
+        let c = rest.chars().next().unwrap_or(' ');
+        let len = if c.is_ascii_digit() || c == '\'' {
+            let len = if c == '\'' {
+                rest[1..].find('\'').map(|p| p + 2).unwrap_or(rest.len())
+            } else {
+                rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len())
+            };
+            let number = parse_number(&rest[..len])
+                .ok_or_else(|| format!("invalid number '{}'", &rest[..len]))?;
+            tokens.push(Token::Number(number));
+            len
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..len].to_string()));
+            len
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            op.len()
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        };
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+fn expand(
+    tokens: &[Token],
+    macros: &HashMap<String, Macro>,
+    hidden: &mut Vec<String>,
+) -> Result<Vec<Token>, String> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        i += 1;
+        let Token::Ident(name) = token else {
+            out.push(token.clone());
+            continue;
+        };
+
+        if name == "defined" {
+            let parenthesised = tokens.get(i) == Some(&Token::Op("("));
+            let at = if parenthesised { i + 1 } else { i };
+            let Some(Token::Ident(target)) = tokens.get(at) else {
+                return Err("expected identifier after 'defined'".to_string());
+            };
+            i = at + 1;
+            if parenthesised {
+                if tokens.get(i) != Some(&Token::Op(")")) {
+                    return Err("expected ')' after 'defined('".to_string());
+                }
+                i += 1;
+            }
+            out.push(Token::Number(macros.contains_key(target) as i64));
+            continue;
+        }
+
+        let Some(m) = macros.get(name).filter(|_| !hidden.contains(name)) else {
+            out.push(token.clone());
+            continue;
+        };
+        let mut body = tokenize(&m.body)?;
+        if let Some(params) = &m.params {
+            if tokens.get(i) != Some(&Token::Op("(")) {
+                out.push(token.clone());
+                continue;
+            }
+            let (args, next) = collect_args(tokens, i + 1)?;
+            i = next;
+            body = body
+                .into_iter()
+                .flat_map(|t| match &t {
+                    Token::Ident(p) => match params.iter().position(|param| param == p) {
+                        Some(index) => args.get(index).cloned().unwrap_or_default(),
+                        None => vec![t],
+                    },
+                    _ => vec![t],
+                })
+                .collect();
+        }
+        hidden.push(name.clone());
+        let expanded = expand(&body, macros, hidden);
+        hidden.pop();
+        out.extend(expanded?);
+    }
+    Ok(out)
+}
+
+fn collect_args(tokens: &[Token], mut i: usize) -> Result<(Vec<Vec<Token>>, usize), String> {
+    let mut args = vec![Vec::new()];
+    let mut depth = 0;
+    while let Some(token) = tokens.get(i) {
+        i += 1;
+        match token {
+            Token::Op("(") => depth += 1,
+            Token::Op(")") if depth == 0 => return Ok((args, i)),
+            Token::Op(")") => depth -= 1,
+            Token::Op(",") if depth == 0 => {
+                args.push(Vec::new());
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(arg) = args.last_mut() {
+            arg.push(token.clone());
+        }
+    }
+    Err("unterminated macro argument list".to_string())
+}
+
+struct ExprParser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    strict: bool,
+    // False in an operand that C does not evaluate, such as the right side
+    // of `1 || x`, where division by zero and undefined symbols are no error.
+    evaluating: bool,
+}
+
+impl ExprParser<'_> {
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), String> {
+        if self.peek_op() == Some(op) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}'", op))
+        }
+    }
+
+    // Parses an operand without evaluating it.
+    fn unevaluated(&mut self, parse: impl FnOnce(&mut Self) -> Result<i64, String>) -> Result<i64, String> {
+        let evaluating = std::mem::replace(&mut self.evaluating, false);
+        let result = parse(self);
+        self.evaluating = evaluating;
+        result
+    }
+
+    fn conditional(&mut self) -> Result<i64, String> {
+        let condition = self.binary(1)?;
+        if self.peek_op() != Some("?") {
+            return Ok(condition);
+        }
+        self.pos += 1;
+        if condition != 0 {
+            let then = self.conditional()?;
+            self.expect(":")?;
+            self.unevaluated(Self::conditional)?;
+            Ok(then)
+        } else {
+            self.unevaluated(Self::conditional)?;
+            self.expect(":")?;
+            self.conditional()
+        }
+    }
+
+    fn binary(&mut self, min_precedence: u8) -> Result<i64, String> {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.peek_op() {
+            let precedence = match op {
+                "||" => 1,
+                "&&" => 2,
+                "|" => 3,
+                "^" => 4,
+                "&" => 5,
+                "==" | "!=" => 6,
+                "<" | ">" | "<=" | ">=" => 7,
+                "<<" | ">>" => 8,
+                "+" | "-" => 9,
+                "*" | "/" | "%" => 10,
+                _ => break,
+            };
+            if precedence < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            let rhs = if matches!((op, lhs != 0), ("||", true) | ("&&", false)) {
+                self.unevaluated(|p| p.binary(precedence + 1))?
+            } else {
+                self.binary(precedence + 1)?
+            };
+            lhs = match op {
+                "||" => (lhs != 0 || rhs != 0) as i64,
+                "&&" => (lhs != 0 && rhs != 0) as i64,
+                "|" => lhs | rhs,
+                "^" => lhs ^ rhs,
+                "&" => lhs & rhs,
+                "==" => (lhs == rhs) as i64,
+                "!=" => (lhs != rhs) as i64,
+                "<" => (lhs < rhs) as i64,
+                ">" => (lhs > rhs) as i64,
+                "<=" => (lhs <= rhs) as i64,
+                ">=" => (lhs >= rhs) as i64,
+                "<<" => lhs.wrapping_shl(rhs as u32),
+                ">>" => lhs.wrapping_shr(rhs as u32),
+                "+" => lhs.wrapping_add(rhs),
+                "-" => lhs.wrapping_sub(rhs),
+                "*" => lhs.wrapping_mul(rhs),
+                _ if rhs == 0 && self.evaluating => return Err("division by zero".to_string()),
+                _ if rhs == 0 => 0,
+                "/" => lhs.wrapping_div(rhs),
+                _ => lhs.wrapping_rem(rhs),
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<i64, String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(n),
+            Some(Token::Ident(name)) if self.strict && self.evaluating => Err(format!("undefined symbol '{}'", name)),
+            // Identifiers left after macro expansion are 0.
+            Some(Token::Ident(_)) => Ok(0),
+            Some(Token::Op("!")) => Ok((self.unary()? == 0) as i64),
+            Some(Token::Op("~")) => Ok(!self.unary()?),
+            Some(Token::Op("-")) => Ok(self.unary()?.wrapping_neg()),
+            Some(Token::Op("+")) => self.unary(),
+            Some(Token::Op("(")) => {
+                let value = self.conditional()?;
+                self.expect(")")?;
+                Ok(value)
+            }
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn macros(defines: &[(&str, &str)]) -> HashMap<String, Macro> {
+        defines
+            .iter()
+            .map(|(name, body)| {
+                (
+                    name.to_string(),
+                    Macro {
+                        params: None,
+                        body: body.to_string(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn short_circuit_skips_the_unevaluated_operand() {
+        let m = macros(&[("A", "0")]);
+        assert_eq!(evaluate("A == 0 || (1 / A)", &m), Ok(1));
+        assert_eq!(evaluate("A && (1 / A)", &m), Ok(0));
+        assert_eq!(evaluate("A ? 1 / A : 2", &m), Ok(2));
+        assert_eq!(evaluate("!A ? 3 : 1 % A", &m), Ok(3));
+        assert_eq!(evaluate_strict("1 || UNDEFINED", &m), Ok(1));
+    }
+
+    #[test]
+    fn evaluated_operands_still_fail() {
+        let m = macros(&[("A", "0")]);
+        assert!(evaluate("A != 0 || (1 / A)", &m).is_err());
+        assert!(evaluate("1 ? 1 / A : 2", &m).is_err());
+        assert!(evaluate("1 || (", &m).is_err());
+    }
+
+    #[test]
+    fn defined_with_and_without_parentheses() {
+        let m = macros(&[("A", "0")]);
+        assert_eq!(evaluate("defined A && defined(A)", &m), Ok(1));
+        assert_eq!(evaluate("defined B || defined ( B )", &m), Ok(0));
+        assert_eq!(evaluate_strict("!defined(B)", &m), Ok(1));
+    }
+
+    fn preprocess(text: &str) -> String {
+        let mut diagnostics = Diagnostics::new();
+        let source = Preprocessor::new(&PreprocessOptions::default(), &mut diagnostics).process_str("test.rc", text);
+        assert!(!diagnostics.has_errors());
+        source.text
+    }
+
+    #[test]
+    fn conditional_blocks() {
+        let text = preprocess("#define A 1\n#if defined(A) && !defined B\nyes\n#else\nno\n#endif\n");
+        assert!(text.contains("yes"));
+        assert!(!text.contains("no"));
+    }
+
+    #[test]
+    fn comment_opened_on_a_directive_line() {
+        let text = preprocess("#define A \"one\" /* starts here\nhidden */ shown A\n");
+        assert!(!text.contains("hidden"));
+        assert!(text.contains("shown \"one\""));
+    }
+
+    #[test]
+    fn object_like_macros_are_expanded_in_the_script() {
+        let text = preprocess(
+            "#define APP_NAME \"My App\"\n#define TITLE APP_NAME\n#define IDD_ABOUT 100\n#define SQUARE(x) \"x\"\n\
+             IDD_ABOUT DIALOG 0, 0, 10, 10\nCAPTION TITLE // APP_NAME\nLTEXT \"APP_NAME\", SQUARE(1)\n",
+        );
+        assert!(text.contains("IDD_ABOUT DIALOG"));
+        assert!(text.contains("CAPTION \"My App\" // APP_NAME"));
+        assert!(text.contains("LTEXT \"APP_NAME\", SQUARE(1)"));
+    }
+}
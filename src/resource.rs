@@ -1,6 +1,48 @@
+use crate::ast::{Expr, VersionInfoDef};
+use crate::diagnostics::Span;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A resource or control ID: the symbol written in the script and, when it
+/// could be resolved, its numeric value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RcId {
+    pub name: String,
+    pub value: Option<i64>,
+}
+
+impl RcId {
+    /// The ID as a C++ expression that compiles without the original resource.h.
+    pub fn to_cpp(&self) -> String {
+        match self.value {
+            Some(value) if value.to_string() != self.name => format!("{} /* {} */", value, self.name),
+            Some(value) => value.to_string(),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl fmt::Display for RcId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// The `LANGUAGE` in effect for a resource.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RcLanguage {
+    pub primary: RcId,
+    pub sub: RcId,
+}
+
+impl fmt::Display for RcLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}", self.primary, self.sub)
+    }
+}
+
 #[derive(Debug)]
 pub struct RcBitmap {
-    pub id: String,
     pub file: String,
     pub width: Option<u32>,
     pub height: Option<u32>,
@@ -13,342 +55,389 @@ pub struct RcBitmap {
     pub author: Option<String>,
 }
 
-pub struct RcBitmapList {
-    pub bitmaps: Vec<RcBitmap>,
+#[derive(Debug)]
+pub struct RcIcon {
+    pub file: String,
 }
 
-impl RcBitmapList {
-    pub fn new() -> Self {
-        RcBitmapList { bitmaps: vec![] }
-    }
+#[derive(Debug)]
+pub struct RcStringTableItem {
+    pub id: RcId,
+    pub text: String,
+    pub span: Span,
+}
 
-    pub fn add(&mut self, bitmap: RcBitmap) {
-        self.bitmaps.push(bitmap);
-    }
+#[derive(Debug)]
+pub struct RcStringTable {
+    pub table: Vec<RcStringTableItem>,
+}
 
-    pub fn output(&self) {
-        println!("void qtMfcInitBitmapResources() {{");
-        println!("    qtMfcBitmapResources.clear();");
-        for bitmap in &self.bitmaps {
-            println!("    // {} BITMAP {}", bitmap.id, bitmap.file);
-            println!(
-                "    qtMfcBitmapResources.insert({}, new CBitmap({}));",
-                bitmap.id, bitmap.file
-            );
-            if let Some(width) = bitmap.width {
-                println!("    // Width: {}", width);
-            }
-            if let Some(height) = bitmap.height {
-                println!("    // Height: {}", height);
-            }
-            if let Some(color_depth) = bitmap.color_depth {
-                println!("    // Color Depth: {}", color_depth);
-            }
-            if let Some(compression) = &bitmap.compression {
-                println!("    // Compression: {}", compression);
-            }
-            if let Some(palette) = &bitmap.palette {
-                println!("    // Palette: {}", palette);
-            }
-            if let Some(dpi) = bitmap.dpi {
-                println!("    // DPI: {}", dpi);
-            }
-            if let Some(color_mode) = &bitmap.color_mode {
-                println!("    // Color Mode: {}", color_mode);
-            }
-            if let Some(compression_level) = bitmap.compression_level {
-                println!("    // Compression Level: {}", compression_level);
-            }
-            if let Some(author) = &bitmap.author {
-                println!("    // Author: {}", author);
-            }
-        }
-        println!("}}");
+impl RcStringTable {
+    pub fn new(table: Vec<RcStringTableItem>) -> Self {
+        RcStringTable { table }
     }
 }
 
+// RcToolbar structure
+#[derive(Debug)]
+pub struct RcToolbar {
+    pub numbers: Vec<i32>,
+    pub table: Vec<String>, // Assuming a simplified type for demonstration
+}
 
-
+// RcAcceleratorTable structure
 #[derive(Debug)]
-pub struct RcIcon {
-    pub id: String,
-    pub file: String,
+pub struct RcAcceleratorTable {
+    pub table: Vec<String>, // Assuming a simplified type for demonstration
 }
 
-pub struct RcIconList {
-    pub icons: Vec<RcIcon>,
+// RcMenu structure
+#[derive(Debug)]
+pub struct RcMenu {
+    pub table: Vec<String>, // Assuming a simplified type for demonstration
 }
 
-impl RcIconList {
-    pub fn new() -> Self {
-        RcIconList { icons: vec![] }
-    }
+/// The FONT statement of a dialog. Weight, italic and charset are only
+/// given in DIALOGEX scripts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RcFont {
+    pub point_size: i32,
+    pub face: String,
+    pub weight: i32,
+    pub italic: bool,
+    pub charset: i32,
+}
 
-    pub fn add(&mut self, icon: RcIcon) {
-        self.icons.push(icon);
-    }
+/// One child control of a dialog, from a CONTROL statement or one of its
+/// shorthands such as LTEXT or PUSHBUTTON.
+#[derive(Debug, Clone)]
+pub struct DialogControl {
+    /// Upper-cased statement keyword.
+    pub kind: String,
+    /// Caption, or the image resource name of ICON and SS_ICON/SS_BITMAP statics.
+    pub text: String,
+    pub id: RcId,
+    /// Window class, given by CONTROL or implied by the keyword.
+    pub class: String,
+    /// Style as written; the defaults implied by the keyword are not included.
+    pub style: Option<Expr>,
+    /// The style the control is created with: the keyword defaults with
+    /// `style` applied.
+    pub effective_style: u32,
+    pub ex_style: Option<Expr>,
+    /// The extended style, with `ex_style` evaluated.
+    pub effective_ex_style: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub help_id: Option<i64>,
+    pub span: Span,
+}
 
-    pub fn output(&self) {
-        println!("void qtInitIconResources() {{");
-        println!("    qtIconNames.clear();");
-        println!("    qtIconResources.clear();");
-        println!("    // Icon with lowest ID value placed first to ensure application icon remains consistent on all systems.");
-        for icon in &self.icons {
-            println!("    // {} ICON {}", icon.id, icon.file);
-            println!("    qtIconNames.insert({}, {});", icon.id, icon.file);
+impl fmt::Display for DialogControl {
+    /// Formats the control with the arguments in CONTROL statement order,
+    /// whatever its keyword.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} \"{}\", {}, \"{}\", {}, {}, {}, {}, {}",
+            self.kind,
+            self.text.replace('"', "\"\""),
+            self.id,
+            self.class,
+            self.style.as_ref().map_or("default".to_string(), |s| s.to_string()),
+            self.x,
+            self.y,
+            self.width,
+            self.height
+        )?;
+        if let Some(ex_style) = &self.ex_style {
+            write!(f, ", {}", ex_style)?;
         }
-        println!("}}");
+        Ok(())
     }
 }
 
+// RcDialogEx structure, also used for legacy DIALOG resources
 #[derive(Debug)]
-pub struct RcStringTableItem {
-    pub id: String,
-    pub text: String,
+pub struct RcDialogEx {
+    /// `false` for a legacy DIALOG.
+    pub extended: bool,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub help_id: Option<i64>,
+    pub caption: String,
+    pub style: Option<Expr>,
+    /// The style the dialog is created with, including the styles implied
+    /// by the absence of STYLE and by CAPTION and FONT.
+    pub effective_style: u32,
+    pub ex_style: Option<Expr>,
+    pub effective_ex_style: u32,
+    pub font: Option<RcFont>,
+    /// Menu bar shown by the dialog.
+    pub menu: Option<RcId>,
+    /// Private window class registered by the application.
+    pub class: Option<String>,
+    pub controls: Vec<DialogControl>,
 }
 
-pub struct RcStringTable {
-    pub table: Vec<RcStringTableItem>,
+/// Initialization data of one dialog control, from a DLGINIT resource.
+#[derive(Debug, Clone)]
+pub struct DialogInitEntry {
+    /// ID of the control, as the 16-bit value Windows uses.
+    pub control: i64,
+    /// The message sent to the control, such as CB_ADDSTRING.
+    pub message: u32,
+    pub data: Vec<u8>,
 }
 
-impl RcStringTable {
-    pub fn new(table: Vec<RcStringTableItem>) -> Self {
-        RcStringTable { table }
-    }
-
-    pub fn output(&self) {
-        for item in &self.table {
-            println!(
-                "    qtMfcStringResources.insert({}, \"{}\");",
-                item.id, item.text
-            );
+impl DialogInitEntry {
+    /// The data as text, up to the terminating NUL. DLGINIT strings are in
+    /// the ANSI code page; bytes that are not UTF-8 are read as Latin-1.
+    pub fn text(&self) -> String {
+        let bytes = match self.data.iter().position(|&b| b == 0) {
+            Some(end) => &self.data[..end],
+            None => &self.data[..],
+        };
+        match std::str::from_utf8(bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => bytes.iter().map(|&b| b as char).collect(),
         }
     }
 }
 
-pub struct RcStringTableList {
-    pub string_tables: Vec<RcStringTable>,
+/// A DLGINIT resource: data sent to the controls of the dialog with the
+/// same ID when it is created, such as combo box items and MFC control
+/// settings.
+#[derive(Debug, Clone, Default)]
+pub struct RcDialogInit {
+    pub entries: Vec<DialogInitEntry>,
 }
 
-impl RcStringTableList {
-    pub fn new() -> Self {
-        RcStringTableList {
-            string_tables: vec![],
-        }
-    }
-
-    pub fn add(&mut self, string_table: RcStringTable) {
-        self.string_tables.push(string_table);
-    }
-
-    pub fn output(&self) {
-        println!("void qtMfcInitStringResources() {{");
-        println!("    qtMfcStringResources.clear();");
-        for string_table in &self.string_tables {
-            println!("    // STRINGTABLE");
-            println!("    // BEGIN");
-            string_table.output();
-            println!("    // END");
-        }
-        println!(
-            "    // AFX resources
-            qtMfcStringResources.insert(AFX_IDS_ALLFILTER, \"All files|\");
-            qtMfcStringResources.insert(AFX_IDS_OPENFILE, \"Open\");
-            qtMfcStringResources.insert(AFX_IDS_SAVEFILE, \"Save As\");
-            qtMfcStringResources.insert(AFX_IDS_SAVEFILECOPY, \"Save As\");
-            qtMfcStringResources.insert(AFX_IDS_UNTITLED, \"Untitled\");
-            qtMfcStringResources.insert(AFX_IDP_ASK_TO_SAVE, \"Save changes to %s?\");
-            qtMfcStringResources.insert(AFX_IDP_FAILED_TO_CREATE_DOC, \"Failed to create empty document.\");
-        ");
-        println!("}}");
+impl RcDialogInit {
+    /// The entries for the control with ID `value`.
+    pub fn for_control(&self, value: i64) -> impl Iterator<Item = &DialogInitEntry> {
+        let control = value as u16 as i64;
+        self.entries.iter().filter(move |entry| entry.control == control)
     }
 }
 
-// Define other structures similarly: RcToolbar, RcAcceleratorTable, RcMenu, RcDialogEx
-// Implement corresponding list structures: RcToolbarList, RcAcceleratorTableList, RcMenuList, RcDialogExList
+/// The typed contents of a resource.
+#[derive(Debug)]
+pub enum RcResourceData {
+    Bitmap(RcBitmap),
+    Icon(RcIcon),
+    StringTable(RcStringTable),
+    Toolbar(RcToolbar),
+    Accelerators(RcAcceleratorTable),
+    Menu(RcMenu),
+    Dialog(RcDialogEx),
+    VersionInfo(VersionInfoDef),
+    DialogInit(RcDialogInit),
+    /// Any other resource read from a file: CURSOR, FONT, HTML, user-defined types...
+    File(String),
+    /// Inline data of RCDATA and user-defined resources.
+    Data(Vec<Expr>),
+}
 
-// RcToolbar structure
+/// One resource of the script, in the order it was defined.
 #[derive(Debug)]
-pub struct RcToolbar {
-    pub id: String,
-    pub numbers: Vec<i32>,
-    pub table: Vec<String>, // Assuming a simplified type for demonstration
+pub struct RcResource {
+    /// Empty for STRINGTABLE, which has no ID of its own.
+    pub id: RcId,
+    /// Upper-cased type keyword (`DIALOGEX`, `ICON`, ...) or user-defined type.
+    pub type_name: String,
+    /// The last `LANGUAGE` statement before the resource, if any.
+    pub language: Option<RcLanguage>,
+    pub memory_flags: Vec<String>,
+    pub span: Span,
+    pub data: RcResourceData,
 }
 
-pub struct RcToolbarList {
-    pub toolbars: Vec<RcToolbar>,
+/// All resources of a script, in definition order, indexed by type and ID.
+#[derive(Debug, Default)]
+pub struct ResourceScript {
+    resources: Vec<RcResource>,
+    by_type: HashMap<String, Vec<usize>>,
+    // Every definition of a type and ID, one per language.
+    by_id: HashMap<(String, String), Vec<usize>>,
 }
 
-impl RcToolbarList {
+impl ResourceScript {
     pub fn new() -> Self {
-        RcToolbarList { toolbars: vec![] }
+        ResourceScript::default()
     }
 
-    pub fn add(&mut self, toolbar: RcToolbar) {
-        self.toolbars.push(toolbar);
+    pub fn add(&mut self, resource: RcResource) {
+        let index = self.resources.len();
+        self.by_type
+            .entry(resource.type_name.clone())
+            .or_default()
+            .push(index);
+        // A resource can be looked up by symbol or by number.
+        let mut keys = vec![resource.id.name.clone()];
+        keys.extend(resource.id.value.map(|v| v.to_string()));
+        for key in keys {
+            self.by_id
+                .entry((id_type(&resource.type_name), key))
+                .or_default()
+                .push(index);
+        }
+        self.resources.push(resource);
     }
 
-    pub fn output(&self) {
-        for toolbar in &self.toolbars {
-            println!(
-                "void qtMfcInitToolBarResource_{}(UINT dlgID, CToolBar* parent) {{",
-                toolbar.id
-            );
-            println!("    // Toolbar logic here...");
-        }
-        println!("void qtMfcInitToolBarResource(UINT dlgID, CToolBar* parent) {{");
-        println!("    switch (dlgID) {{");
-        for toolbar in &self.toolbars {
-            println!("        case {}:", toolbar.id);
-            println!(
-                "            qtMfcInitToolBarResource_{}(dlgID, parent);",
-                toolbar.id
-            );
-            println!("            break;");
-        }
-        println!("    }}");
-        println!("}}");
+    pub fn resources(&self) -> &[RcResource] {
+        &self.resources
     }
-}
 
-// RcAcceleratorTable structure
-#[derive(Debug)]
-pub struct RcAcceleratorTable {
-    pub id: String,
-    pub table: Vec<String>, // Assuming a simplified type for demonstration
-}
+    /// Finds a resource by type and by ID, given as a symbol or a number.
+    /// Of a resource defined for several languages, the first one is returned.
+    pub fn get(&self, type_name: &str, id: &str) -> Option<&RcResource> {
+        self.definitions(type_name, id).next()
+    }
 
-pub struct RcAcceleratorTableList {
-    pub accelerator_tables: Vec<RcAcceleratorTable>,
-}
+    /// Finds the resource of a type and ID defined for `language`.
+    pub fn get_in(&self, type_name: &str, id: &str, language: Option<&RcLanguage>) -> Option<&RcResource> {
+        self.definitions(type_name, id)
+            .find(|r| r.language.as_ref() == language)
+    }
 
-impl RcAcceleratorTableList {
-    pub fn new() -> Self {
-        RcAcceleratorTableList {
-            accelerator_tables: vec![],
-        }
+    fn definitions<'a>(&'a self, type_name: &str, id: &str) -> impl Iterator<Item = &'a RcResource> {
+        self.by_id
+            .get(&(id_type(type_name), id.to_string()))
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.resources[index])
     }
 
-    pub fn add(&mut self, accelerator_table: RcAcceleratorTable) {
-        self.accelerator_tables.push(accelerator_table);
+    /// All resources of one type, in definition order.
+    pub fn of_type<'a>(&'a self, type_name: &str) -> impl Iterator<Item = &'a RcResource> {
+        self.by_type
+            .get(&type_name.to_ascii_uppercase())
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.resources[index])
     }
 
-    pub fn output(&self) {
-        for accelerator_table in &self.accelerator_tables {
-            println!("// {} ACCELERATORS", accelerator_table.id);
-            println!("ACCEL ACCEL_{}[] = {{", accelerator_table.id);
-            println!("    // Accelerator table logic here...");
-            println!("    {{ 0, 0, 0 }},");
-            println!("}};");
-        }
-        println!("ACCEL* qtMfcAcceleratorResource(UINT id) {{");
-        println!("    switch (id) {{");
-        for accelerator_table in &self.accelerator_tables {
-            println!("        case {}:", accelerator_table.id);
-            println!("            return ACCEL_{};", accelerator_table.id);
-            println!("            break;");
-        }
-        println!("    }}");
-        println!("    return NULL;");
-        println!("}}");
+    pub fn bitmaps(&self) -> impl Iterator<Item = (&RcResource, &RcBitmap)> {
+        self.resources.iter().filter_map(|r| match &r.data {
+            RcResourceData::Bitmap(bitmap) => Some((r, bitmap)),
+            _ => None,
+        })
     }
-}
 
-// RcMenu structure
-#[derive(Debug)]
-pub struct RcMenu {
-    pub id: String,
-    pub table: Vec<String>, // Assuming a simplified type for demonstration
-}
+    pub fn icons(&self) -> impl Iterator<Item = (&RcResource, &RcIcon)> {
+        self.resources.iter().filter_map(|r| match &r.data {
+            RcResourceData::Icon(icon) => Some((r, icon)),
+            _ => None,
+        })
+    }
 
-pub struct RcMenuList {
-    pub menus: Vec<RcMenu>,
-}
+    pub fn string_tables(&self) -> impl Iterator<Item = (&RcResource, &RcStringTable)> {
+        self.resources.iter().filter_map(|r| match &r.data {
+            RcResourceData::StringTable(table) => Some((r, table)),
+            _ => None,
+        })
+    }
 
-impl RcMenuList {
-    pub fn new() -> Self {
-        RcMenuList { menus: vec![] }
+    pub fn toolbars(&self) -> impl Iterator<Item = (&RcResource, &RcToolbar)> {
+        self.resources.iter().filter_map(|r| match &r.data {
+            RcResourceData::Toolbar(toolbar) => Some((r, toolbar)),
+            _ => None,
+        })
     }
 
-    pub fn add(&mut self, menu: RcMenu) {
-        self.menus.push(menu);
+    pub fn accelerator_tables(&self) -> impl Iterator<Item = (&RcResource, &RcAcceleratorTable)> {
+        self.resources.iter().filter_map(|r| match &r.data {
+            RcResourceData::Accelerators(table) => Some((r, table)),
+            _ => None,
+        })
     }
 
-    pub fn output(&self) {
-        for menu in &self.menus {
-            println!("void qtMfcInitMenuResource_{}(CMenu* parent) {{", menu.id);
-            println!("    // Menu logic here...");
-        }
-        println!("void qtMfcInitMenuResource(UINT menuID, CMenu* parent) {{");
-        println!("    switch (menuID) {{");
-        for menu in &self.menus {
-            println!("        case {}:", menu.id);
-            println!("            qtMfcInitMenuResource_{}(parent);", menu.id);
-            println!("            break;");
+    pub fn menus(&self) -> impl Iterator<Item = (&RcResource, &RcMenu)> {
+        self.resources.iter().filter_map(|r| match &r.data {
+            RcResourceData::Menu(menu) => Some((r, menu)),
+            _ => None,
+        })
+    }
+
+    pub fn dialogs(&self) -> impl Iterator<Item = (&RcResource, &RcDialogEx)> {
+        self.resources.iter().filter_map(|r| match &r.data {
+            RcResourceData::Dialog(dialog) => Some((r, dialog)),
+            _ => None,
+        })
+    }
+
+    /// The DLGINIT data of the dialog with ID `id`, if it has any. The
+    /// DLGINIT may name the dialog by its symbol or by its number.
+    pub fn dialog_init(&self, id: &RcId) -> Option<&RcDialogInit> {
+        let resource = self
+            .get("DLGINIT", &id.name)
+            .or_else(|| self.get("DLGINIT", &id.value?.to_string()))?;
+        match &resource.data {
+            RcResourceData::DialogInit(init) => Some(init),
+            _ => None,
         }
-        println!("    }}");
-        println!("    // Fixup shortcuts");
-        println!("    int menu = 0;");
-        println!("    CMenu* subMenu = parent->GetSubMenu(menu);");
-        println!("    while (subMenu) {{");
-        println!("        foreach (QAction* action, subMenu->toQMenu()->actions()) {{");
-        println!("            if (action->text().contains(\"\\t\")) {{");
-        println!("                action->setShortcut(QKeySequence(action->text().split(\"\\t\").at(1)));");
-        println!("            }}");
-        println!("        }}");
-        println!("        menu++;");
-        println!("        subMenu = parent->GetSubMenu(menu);");
-        println!("    }}");
-        println!("}}");
     }
 }
 
-// RcDialogEx structure
-#[derive(Debug)]
-pub struct RcDialogEx {
-    pub id: String,
-    pub items: Vec<String>,   // Assuming a simplified type for demonstration
-    pub options: Vec<String>, // Assuming a simplified type for demonstration
-    pub numbers: Vec<i32>,
-    pub caption: String,
-    pub style: String,
-    pub font: String,
+// The type under which resources are indexed by ID. DIALOG and DIALOGEX,
+// like MENU and MENUEX, define the same type of resource.
+fn id_type(type_name: &str) -> String {
+    match type_name.to_ascii_uppercase().as_str() {
+        "DIALOGEX" => "DIALOG".to_string(),
+        "MENUEX" => "MENU".to_string(),
+        other => other.to_string(),
+    }
 }
 
-pub struct RcDialogExList {
-    pub dialogs: Vec<RcDialogEx>,
-}
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::{Diagnostics, Severity};
+    use crate::{parse_str, PreprocessOptions, ResourceScript};
 
-impl RcDialogExList {
-    pub fn new() -> Self {
-        RcDialogExList { dialogs: vec![] }
+    fn parse(text: &str) -> (ResourceScript, Diagnostics) {
+        let mut diagnostics = Diagnostics::new();
+        let script = parse_str(text, &PreprocessOptions::default(), &mut diagnostics).unwrap();
+        (script, diagnostics)
     }
 
-    pub fn add(&mut self, dialog: RcDialogEx) {
-        self.dialogs.push(dialog);
+    #[test]
+    fn resources_are_found_by_name_number_and_language() {
+        let (script, diagnostics) = parse(
+            "#define IDR_MAIN 128\n\
+             LANGUAGE LANG_ENGLISH, SUBLANG_ENGLISH_US\n\
+             IDR_MAIN MENU\nBEGIN\n  MENUITEM \"Open\", 10\nEND\n\
+             LANGUAGE LANG_FRENCH, SUBLANG_FRENCH\n\
+             IDR_MAIN MENU\nBEGIN\n  MENUITEM \"Ouvrir\", 10\nEND\n",
+        );
+        assert_eq!(diagnostics.count(Severity::Warning), 0);
+        let [english, french] = script.resources() else {
+            panic!("expected two menus");
+        };
+        assert!(std::ptr::eq(script.get("MENU", "IDR_MAIN").unwrap(), english));
+        assert!(std::ptr::eq(script.get("menu", "128").unwrap(), english));
+        let found = script.get_in("MENU", "128", french.language.as_ref()).unwrap();
+        assert!(std::ptr::eq(found, french));
+        assert!(script.get_in("MENU", "128", None).is_none());
+        assert_eq!(script.of_type("MENU").count(), 2);
     }
 
-    pub fn output(&self) {
-        for dialog in &self.dialogs {
-            println!(
-                "void qtMfcInitDialogResource_{}(CDialog* parent) {{",
-                dialog.id
-            );
-            println!("    // Dialog logic here...");
-        }
-        println!("void qtMfcInitDialogResource(UINT dlgID, CDialog* parent) {{");
-        println!("    switch (dlgID) {{");
-        for dialog in &self.dialogs {
-            println!("        case {}:", dialog.id);
-            println!("            qtMfcInitDialogResource_{}(parent);", dialog.id);
-            println!("            break;");
-        }
-        println!("        case 0:");
-        println!("            // Allow blank dialogs.");
-        println!("            break;");
-        println!("        default:");
-        println!("            qFatal(\"dialog resource not implemented...\");");
-        println!("    }}");
-        println!("}}");
+    #[test]
+    fn extended_types_share_ids_with_their_plain_types() {
+        let (script, diagnostics) = parse(
+            "1 DIALOG 0, 0, 10, 10\nBEGIN\nEND\n\
+             1 DIALOGEX 0, 0, 10, 10\nBEGIN\nEND\n\
+             2 MENUEX\nBEGIN\n  MENUITEM \"Open\", 10\nEND\n\
+             2 MENU\nBEGIN\n  MENUITEM \"Open\", 10\nEND\n",
+        );
+        assert_eq!(diagnostics.count(Severity::Warning), 2);
+        assert!(diagnostics.render().contains("duplicate DIALOGEX resource 1"));
+        assert!(diagnostics.render().contains("duplicate MENU resource 2"));
+        assert_eq!(script.get("DIALOGEX", "1").unwrap().type_name, "DIALOG");
+        assert_eq!(script.get("MENU", "2").unwrap().type_name, "MENUEX");
     }
 }
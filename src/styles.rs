@@ -0,0 +1,707 @@
+use crate::ast::{BinaryOp, Expr, UnaryOp};
+use crate::diagnostics::{Diagnostics, Span};
+use crate::symbols::SymbolTable;
+
+// Window styles from winuser.h.
+pub const WS_POPUP: u32 = 0x8000_0000;
+pub const WS_CHILD: u32 = 0x4000_0000;
+pub const WS_MINIMIZE: u32 = 0x2000_0000;
+pub const WS_VISIBLE: u32 = 0x1000_0000;
+pub const WS_DISABLED: u32 = 0x0800_0000;
+pub const WS_CLIPSIBLINGS: u32 = 0x0400_0000;
+pub const WS_CLIPCHILDREN: u32 = 0x0200_0000;
+pub const WS_MAXIMIZE: u32 = 0x0100_0000;
+pub const WS_CAPTION: u32 = 0x00C0_0000;
+pub const WS_BORDER: u32 = 0x0080_0000;
+pub const WS_DLGFRAME: u32 = 0x0040_0000;
+pub const WS_VSCROLL: u32 = 0x0020_0000;
+pub const WS_HSCROLL: u32 = 0x0010_0000;
+pub const WS_SYSMENU: u32 = 0x0008_0000;
+pub const WS_THICKFRAME: u32 = 0x0004_0000;
+pub const WS_GROUP: u32 = 0x0002_0000;
+pub const WS_TABSTOP: u32 = 0x0001_0000;
+pub const WS_MINIMIZEBOX: u32 = 0x0002_0000;
+pub const WS_MAXIMIZEBOX: u32 = 0x0001_0000;
+
+// Extended window styles from winuser.h.
+pub const WS_EX_DLGMODALFRAME: u32 = 0x1;
+pub const WS_EX_NOPARENTNOTIFY: u32 = 0x4;
+pub const WS_EX_TOPMOST: u32 = 0x8;
+pub const WS_EX_ACCEPTFILES: u32 = 0x10;
+pub const WS_EX_TRANSPARENT: u32 = 0x20;
+pub const WS_EX_MDICHILD: u32 = 0x40;
+pub const WS_EX_TOOLWINDOW: u32 = 0x80;
+pub const WS_EX_WINDOWEDGE: u32 = 0x100;
+pub const WS_EX_CLIENTEDGE: u32 = 0x200;
+pub const WS_EX_CONTEXTHELP: u32 = 0x400;
+pub const WS_EX_RIGHT: u32 = 0x1000;
+pub const WS_EX_RTLREADING: u32 = 0x2000;
+pub const WS_EX_LEFTSCROLLBAR: u32 = 0x4000;
+pub const WS_EX_CONTROLPARENT: u32 = 0x1_0000;
+pub const WS_EX_STATICEDGE: u32 = 0x2_0000;
+pub const WS_EX_APPWINDOW: u32 = 0x4_0000;
+pub const WS_EX_LAYERED: u32 = 0x8_0000;
+pub const WS_EX_NOINHERITLAYOUT: u32 = 0x10_0000;
+pub const WS_EX_LAYOUTRTL: u32 = 0x40_0000;
+pub const WS_EX_COMPOSITED: u32 = 0x200_0000;
+pub const WS_EX_NOACTIVATE: u32 = 0x800_0000;
+
+// Dialog styles.
+pub const DS_ABSALIGN: u32 = 0x1;
+pub const DS_SYSMODAL: u32 = 0x2;
+pub const DS_3DLOOK: u32 = 0x4;
+pub const DS_FIXEDSYS: u32 = 0x8;
+pub const DS_NOFAILCREATE: u32 = 0x10;
+pub const DS_LOCALEDIT: u32 = 0x20;
+pub const DS_SETFONT: u32 = 0x40;
+pub const DS_MODALFRAME: u32 = 0x80;
+pub const DS_NOIDLEMSG: u32 = 0x100;
+pub const DS_SETFOREGROUND: u32 = 0x200;
+pub const DS_CONTROL: u32 = 0x400;
+pub const DS_CENTER: u32 = 0x800;
+pub const DS_CENTERMOUSE: u32 = 0x1000;
+pub const DS_CONTEXTHELP: u32 = 0x2000;
+
+// Button styles.
+pub const BS_PUSHBUTTON: u32 = 0x0;
+pub const BS_DEFPUSHBUTTON: u32 = 0x1;
+pub const BS_CHECKBOX: u32 = 0x2;
+pub const BS_AUTOCHECKBOX: u32 = 0x3;
+pub const BS_RADIOBUTTON: u32 = 0x4;
+pub const BS_3STATE: u32 = 0x5;
+pub const BS_AUTO3STATE: u32 = 0x6;
+pub const BS_GROUPBOX: u32 = 0x7;
+pub const BS_USERBUTTON: u32 = 0x8;
+pub const BS_AUTORADIOBUTTON: u32 = 0x9;
+pub const BS_PUSHBOX: u32 = 0xA;
+pub const BS_OWNERDRAW: u32 = 0xB;
+pub const BS_SPLITBUTTON: u32 = 0xC;
+pub const BS_DEFSPLITBUTTON: u32 = 0xD;
+pub const BS_COMMANDLINK: u32 = 0xE;
+pub const BS_DEFCOMMANDLINK: u32 = 0xF;
+pub const BS_TYPEMASK: u32 = 0xF;
+pub const BS_LEFTTEXT: u32 = 0x20;
+pub const BS_ICON: u32 = 0x40;
+pub const BS_BITMAP: u32 = 0x80;
+pub const BS_LEFT: u32 = 0x100;
+pub const BS_RIGHT: u32 = 0x200;
+pub const BS_CENTER: u32 = 0x300;
+pub const BS_TOP: u32 = 0x400;
+pub const BS_BOTTOM: u32 = 0x800;
+pub const BS_VCENTER: u32 = 0xC00;
+pub const BS_PUSHLIKE: u32 = 0x1000;
+pub const BS_MULTILINE: u32 = 0x2000;
+pub const BS_NOTIFY: u32 = 0x4000;
+pub const BS_FLAT: u32 = 0x8000;
+
+// Edit styles.
+pub const ES_LEFT: u32 = 0x0;
+pub const ES_CENTER: u32 = 0x1;
+pub const ES_RIGHT: u32 = 0x2;
+pub const ES_MULTILINE: u32 = 0x4;
+pub const ES_UPPERCASE: u32 = 0x8;
+pub const ES_LOWERCASE: u32 = 0x10;
+pub const ES_PASSWORD: u32 = 0x20;
+pub const ES_AUTOVSCROLL: u32 = 0x40;
+pub const ES_AUTOHSCROLL: u32 = 0x80;
+pub const ES_NOHIDESEL: u32 = 0x100;
+pub const ES_OEMCONVERT: u32 = 0x400;
+pub const ES_READONLY: u32 = 0x800;
+pub const ES_WANTRETURN: u32 = 0x1000;
+pub const ES_NUMBER: u32 = 0x2000;
+
+// Static styles.
+pub const SS_LEFT: u32 = 0x0;
+pub const SS_CENTER: u32 = 0x1;
+pub const SS_RIGHT: u32 = 0x2;
+pub const SS_ICON: u32 = 0x3;
+pub const SS_BLACKRECT: u32 = 0x4;
+pub const SS_GRAYRECT: u32 = 0x5;
+pub const SS_WHITERECT: u32 = 0x6;
+pub const SS_BLACKFRAME: u32 = 0x7;
+pub const SS_GRAYFRAME: u32 = 0x8;
+pub const SS_WHITEFRAME: u32 = 0x9;
+pub const SS_USERITEM: u32 = 0xA;
+pub const SS_SIMPLE: u32 = 0xB;
+pub const SS_LEFTNOWORDWRAP: u32 = 0xC;
+pub const SS_OWNERDRAW: u32 = 0xD;
+pub const SS_BITMAP: u32 = 0xE;
+pub const SS_ENHMETAFILE: u32 = 0xF;
+pub const SS_ETCHEDHORZ: u32 = 0x10;
+pub const SS_ETCHEDVERT: u32 = 0x11;
+pub const SS_ETCHEDFRAME: u32 = 0x12;
+pub const SS_TYPEMASK: u32 = 0x1F;
+pub const SS_REALSIZECONTROL: u32 = 0x40;
+pub const SS_NOPREFIX: u32 = 0x80;
+pub const SS_NOTIFY: u32 = 0x100;
+pub const SS_CENTERIMAGE: u32 = 0x200;
+pub const SS_RIGHTJUST: u32 = 0x400;
+pub const SS_REALSIZEIMAGE: u32 = 0x800;
+pub const SS_SUNKEN: u32 = 0x1000;
+pub const SS_EDITCONTROL: u32 = 0x2000;
+pub const SS_ENDELLIPSIS: u32 = 0x4000;
+pub const SS_PATHELLIPSIS: u32 = 0x8000;
+pub const SS_WORDELLIPSIS: u32 = 0xC000;
+
+// Combo box styles.
+pub const CBS_SIMPLE: u32 = 0x1;
+pub const CBS_DROPDOWN: u32 = 0x2;
+pub const CBS_DROPDOWNLIST: u32 = 0x3;
+pub const CBS_OWNERDRAWFIXED: u32 = 0x10;
+pub const CBS_OWNERDRAWVARIABLE: u32 = 0x20;
+pub const CBS_AUTOHSCROLL: u32 = 0x40;
+pub const CBS_OEMCONVERT: u32 = 0x80;
+pub const CBS_SORT: u32 = 0x100;
+pub const CBS_HASSTRINGS: u32 = 0x200;
+pub const CBS_NOINTEGRALHEIGHT: u32 = 0x400;
+pub const CBS_DISABLENOSCROLL: u32 = 0x800;
+pub const CBS_UPPERCASE: u32 = 0x2000;
+pub const CBS_LOWERCASE: u32 = 0x4000;
+
+// List box styles.
+pub const LBS_NOTIFY: u32 = 0x1;
+pub const LBS_SORT: u32 = 0x2;
+pub const LBS_NOREDRAW: u32 = 0x4;
+pub const LBS_MULTIPLESEL: u32 = 0x8;
+pub const LBS_OWNERDRAWFIXED: u32 = 0x10;
+pub const LBS_OWNERDRAWVARIABLE: u32 = 0x20;
+pub const LBS_HASSTRINGS: u32 = 0x40;
+pub const LBS_USETABSTOPS: u32 = 0x80;
+pub const LBS_NOINTEGRALHEIGHT: u32 = 0x100;
+pub const LBS_MULTICOLUMN: u32 = 0x200;
+pub const LBS_WANTKEYBOARDINPUT: u32 = 0x400;
+pub const LBS_EXTENDEDSEL: u32 = 0x800;
+pub const LBS_DISABLENOSCROLL: u32 = 0x1000;
+pub const LBS_NODATA: u32 = 0x2000;
+pub const LBS_NOSEL: u32 = 0x4000;
+pub const LBS_COMBOBOX: u32 = 0x8000;
+
+// Scroll bar styles.
+pub const SBS_HORZ: u32 = 0x0;
+pub const SBS_VERT: u32 = 0x1;
+pub const SBS_TOPALIGN: u32 = 0x2;
+pub const SBS_LEFTALIGN: u32 = 0x2;
+pub const SBS_BOTTOMALIGN: u32 = 0x4;
+pub const SBS_RIGHTALIGN: u32 = 0x4;
+pub const SBS_SIZEBOX: u32 = 0x8;
+pub const SBS_SIZEGRIP: u32 = 0x10;
+
+// List view styles from commctrl.h.
+pub const LVS_ICON: u32 = 0x0;
+pub const LVS_REPORT: u32 = 0x1;
+pub const LVS_SMALLICON: u32 = 0x2;
+pub const LVS_LIST: u32 = 0x3;
+pub const LVS_TYPEMASK: u32 = 0x3;
+pub const LVS_SINGLESEL: u32 = 0x4;
+pub const LVS_SHOWSELALWAYS: u32 = 0x8;
+pub const LVS_SORTASCENDING: u32 = 0x10;
+pub const LVS_SORTDESCENDING: u32 = 0x20;
+pub const LVS_SHAREIMAGELISTS: u32 = 0x40;
+pub const LVS_NOLABELWRAP: u32 = 0x80;
+pub const LVS_AUTOARRANGE: u32 = 0x100;
+pub const LVS_EDITLABELS: u32 = 0x200;
+pub const LVS_OWNERDRAWFIXED: u32 = 0x400;
+pub const LVS_ALIGNLEFT: u32 = 0x800;
+pub const LVS_OWNERDATA: u32 = 0x1000;
+pub const LVS_NOSCROLL: u32 = 0x2000;
+pub const LVS_NOCOLUMNHEADER: u32 = 0x4000;
+pub const LVS_NOSORTHEADER: u32 = 0x8000;
+
+// Tree view styles.
+pub const TVS_HASBUTTONS: u32 = 0x1;
+pub const TVS_HASLINES: u32 = 0x2;
+pub const TVS_LINESATROOT: u32 = 0x4;
+pub const TVS_EDITLABELS: u32 = 0x8;
+pub const TVS_DISABLEDRAGDROP: u32 = 0x10;
+pub const TVS_SHOWSELALWAYS: u32 = 0x20;
+pub const TVS_RTLREADING: u32 = 0x40;
+pub const TVS_NOTOOLTIPS: u32 = 0x80;
+pub const TVS_CHECKBOXES: u32 = 0x100;
+pub const TVS_TRACKSELECT: u32 = 0x200;
+pub const TVS_SINGLEEXPAND: u32 = 0x400;
+pub const TVS_INFOTIP: u32 = 0x800;
+pub const TVS_FULLROWSELECT: u32 = 0x1000;
+pub const TVS_NOSCROLL: u32 = 0x2000;
+pub const TVS_NONEVENHEIGHT: u32 = 0x4000;
+pub const TVS_NOHSCROLL: u32 = 0x8000;
+
+// Tab control styles.
+pub const TCS_SCROLLOPPOSITE: u32 = 0x1;
+pub const TCS_BOTTOM: u32 = 0x2;
+pub const TCS_RIGHT: u32 = 0x2;
+pub const TCS_MULTISELECT: u32 = 0x4;
+pub const TCS_FLATBUTTONS: u32 = 0x8;
+pub const TCS_FORCEICONLEFT: u32 = 0x10;
+pub const TCS_FORCELABELLEFT: u32 = 0x20;
+pub const TCS_HOTTRACK: u32 = 0x40;
+pub const TCS_VERTICAL: u32 = 0x80;
+pub const TCS_BUTTONS: u32 = 0x100;
+pub const TCS_MULTILINE: u32 = 0x200;
+pub const TCS_FIXEDWIDTH: u32 = 0x400;
+pub const TCS_RAGGEDRIGHT: u32 = 0x800;
+pub const TCS_FOCUSONBUTTONDOWN: u32 = 0x1000;
+pub const TCS_OWNERDRAWFIXED: u32 = 0x2000;
+pub const TCS_TOOLTIPS: u32 = 0x4000;
+pub const TCS_FOCUSNEVER: u32 = 0x8000;
+
+// Progress bar styles.
+pub const PBS_SMOOTH: u32 = 0x1;
+pub const PBS_VERTICAL: u32 = 0x4;
+pub const PBS_MARQUEE: u32 = 0x8;
+pub const PBS_SMOOTHREVERSE: u32 = 0x10;
+
+// Trackbar styles.
+pub const TBS_AUTOTICKS: u32 = 0x1;
+pub const TBS_VERT: u32 = 0x2;
+pub const TBS_HORZ: u32 = 0x0;
+pub const TBS_TOP: u32 = 0x4;
+pub const TBS_BOTTOM: u32 = 0x0;
+pub const TBS_LEFT: u32 = 0x4;
+pub const TBS_RIGHT: u32 = 0x0;
+pub const TBS_BOTH: u32 = 0x8;
+pub const TBS_NOTICKS: u32 = 0x10;
+pub const TBS_ENABLESELRANGE: u32 = 0x20;
+pub const TBS_FIXEDLENGTH: u32 = 0x40;
+pub const TBS_NOTHUMB: u32 = 0x80;
+pub const TBS_TOOLTIPS: u32 = 0x100;
+pub const TBS_REVERSED: u32 = 0x200;
+pub const TBS_DOWNISLEFT: u32 = 0x400;
+pub const TBS_NOTIFYBEFOREMOVE: u32 = 0x800;
+pub const TBS_TRANSPARENTBKGND: u32 = 0x1000;
+
+// Up-down styles.
+pub const UDS_WRAP: u32 = 0x1;
+pub const UDS_SETBUDDYINT: u32 = 0x2;
+pub const UDS_ALIGNRIGHT: u32 = 0x4;
+pub const UDS_ALIGNLEFT: u32 = 0x8;
+pub const UDS_AUTOBUDDY: u32 = 0x10;
+pub const UDS_ARROWKEYS: u32 = 0x20;
+pub const UDS_HORZ: u32 = 0x40;
+pub const UDS_NOTHOUSANDS: u32 = 0x80;
+pub const UDS_HOTTRACK: u32 = 0x100;
+
+// Date and time picker styles.
+pub const DTS_UPDOWN: u32 = 0x1;
+pub const DTS_SHOWNONE: u32 = 0x2;
+pub const DTS_SHORTDATEFORMAT: u32 = 0x0;
+pub const DTS_LONGDATEFORMAT: u32 = 0x4;
+pub const DTS_SHORTDATECENTURYFORMAT: u32 = 0xC;
+pub const DTS_TIMEFORMAT: u32 = 0x9;
+pub const DTS_APPCANPARSE: u32 = 0x10;
+pub const DTS_RIGHTALIGN: u32 = 0x20;
+
+// Month calendar styles.
+pub const MCS_DAYSTATE: u32 = 0x1;
+pub const MCS_MULTISELECT: u32 = 0x2;
+pub const MCS_WEEKNUMBERS: u32 = 0x4;
+pub const MCS_NOTODAYCIRCLE: u32 = 0x8;
+pub const MCS_NOTODAY: u32 = 0x10;
+pub const MCS_NOTRAILINGDATES: u32 = 0x40;
+pub const MCS_SHORTDAYSOFWEEK: u32 = 0x80;
+pub const MCS_NOSELCHANGEONNAV: u32 = 0x100;
+
+// SysLink styles.
+pub const LWS_TRANSPARENT: u32 = 0x1;
+pub const LWS_IGNORERETURN: u32 = 0x2;
+pub const LWS_NOPREFIX: u32 = 0x4;
+pub const LWS_USEVISUALSTYLE: u32 = 0x8;
+pub const LWS_USECUSTOMTEXT: u32 = 0x10;
+pub const LWS_RIGHT: u32 = 0x20;
+
+const STYLE_CONSTANTS: &[(&str, u32)] = &[
+    ("WS_OVERLAPPED", 0),
+    ("WS_TILED", 0),
+    ("WS_POPUP", WS_POPUP),
+    ("WS_CHILD", WS_CHILD),
+    ("WS_CHILDWINDOW", WS_CHILD),
+    ("WS_MINIMIZE", WS_MINIMIZE),
+    ("WS_ICONIC", WS_MINIMIZE),
+    ("WS_VISIBLE", WS_VISIBLE),
+    ("WS_DISABLED", WS_DISABLED),
+    ("WS_CLIPSIBLINGS", WS_CLIPSIBLINGS),
+    ("WS_CLIPCHILDREN", WS_CLIPCHILDREN),
+    ("WS_MAXIMIZE", WS_MAXIMIZE),
+    ("WS_CAPTION", WS_CAPTION),
+    ("WS_BORDER", WS_BORDER),
+    ("WS_DLGFRAME", WS_DLGFRAME),
+    ("WS_VSCROLL", WS_VSCROLL),
+    ("WS_HSCROLL", WS_HSCROLL),
+    ("WS_SYSMENU", WS_SYSMENU),
+    ("WS_THICKFRAME", WS_THICKFRAME),
+    ("WS_SIZEBOX", WS_THICKFRAME),
+    ("WS_GROUP", WS_GROUP),
+    ("WS_TABSTOP", WS_TABSTOP),
+    ("WS_MINIMIZEBOX", WS_MINIMIZEBOX),
+    ("WS_MAXIMIZEBOX", WS_MAXIMIZEBOX),
+    ("WS_OVERLAPPEDWINDOW", WS_CAPTION | WS_SYSMENU | WS_THICKFRAME | WS_MINIMIZEBOX | WS_MAXIMIZEBOX),
+    ("WS_TILEDWINDOW", WS_CAPTION | WS_SYSMENU | WS_THICKFRAME | WS_MINIMIZEBOX | WS_MAXIMIZEBOX),
+    ("WS_POPUPWINDOW", WS_POPUP | WS_BORDER | WS_SYSMENU),
+    ("WS_EX_DLGMODALFRAME", WS_EX_DLGMODALFRAME),
+    ("WS_EX_NOPARENTNOTIFY", WS_EX_NOPARENTNOTIFY),
+    ("WS_EX_TOPMOST", WS_EX_TOPMOST),
+    ("WS_EX_ACCEPTFILES", WS_EX_ACCEPTFILES),
+    ("WS_EX_TRANSPARENT", WS_EX_TRANSPARENT),
+    ("WS_EX_MDICHILD", WS_EX_MDICHILD),
+    ("WS_EX_TOOLWINDOW", WS_EX_TOOLWINDOW),
+    ("WS_EX_WINDOWEDGE", WS_EX_WINDOWEDGE),
+    ("WS_EX_CLIENTEDGE", WS_EX_CLIENTEDGE),
+    ("WS_EX_CONTEXTHELP", WS_EX_CONTEXTHELP),
+    ("WS_EX_RIGHT", WS_EX_RIGHT),
+    ("WS_EX_LEFT", 0),
+    ("WS_EX_RTLREADING", WS_EX_RTLREADING),
+    ("WS_EX_LTRREADING", 0),
+    ("WS_EX_LEFTSCROLLBAR", WS_EX_LEFTSCROLLBAR),
+    ("WS_EX_RIGHTSCROLLBAR", 0),
+    ("WS_EX_CONTROLPARENT", WS_EX_CONTROLPARENT),
+    ("WS_EX_STATICEDGE", WS_EX_STATICEDGE),
+    ("WS_EX_APPWINDOW", WS_EX_APPWINDOW),
+    ("WS_EX_OVERLAPPEDWINDOW", WS_EX_WINDOWEDGE | WS_EX_CLIENTEDGE),
+    ("WS_EX_PALETTEWINDOW", WS_EX_WINDOWEDGE | WS_EX_TOOLWINDOW | WS_EX_TOPMOST),
+    ("WS_EX_LAYERED", WS_EX_LAYERED),
+    ("WS_EX_NOINHERITLAYOUT", WS_EX_NOINHERITLAYOUT),
+    ("WS_EX_LAYOUTRTL", WS_EX_LAYOUTRTL),
+    ("WS_EX_COMPOSITED", WS_EX_COMPOSITED),
+    ("WS_EX_NOACTIVATE", WS_EX_NOACTIVATE),
+    ("DS_ABSALIGN", DS_ABSALIGN),
+    ("DS_SYSMODAL", DS_SYSMODAL),
+    ("DS_3DLOOK", DS_3DLOOK),
+    ("DS_FIXEDSYS", DS_FIXEDSYS),
+    ("DS_NOFAILCREATE", DS_NOFAILCREATE),
+    ("DS_LOCALEDIT", DS_LOCALEDIT),
+    ("DS_SETFONT", DS_SETFONT),
+    ("DS_MODALFRAME", DS_MODALFRAME),
+    ("DS_NOIDLEMSG", DS_NOIDLEMSG),
+    ("DS_SETFOREGROUND", DS_SETFOREGROUND),
+    ("DS_CONTROL", DS_CONTROL),
+    ("DS_CENTER", DS_CENTER),
+    ("DS_CENTERMOUSE", DS_CENTERMOUSE),
+    ("DS_CONTEXTHELP", DS_CONTEXTHELP),
+    ("DS_SHELLFONT", DS_SETFONT | DS_FIXEDSYS),
+    ("BS_PUSHBUTTON", BS_PUSHBUTTON),
+    ("BS_DEFPUSHBUTTON", BS_DEFPUSHBUTTON),
+    ("BS_CHECKBOX", BS_CHECKBOX),
+    ("BS_AUTOCHECKBOX", BS_AUTOCHECKBOX),
+    ("BS_RADIOBUTTON", BS_RADIOBUTTON),
+    ("BS_3STATE", BS_3STATE),
+    ("BS_AUTO3STATE", BS_AUTO3STATE),
+    ("BS_GROUPBOX", BS_GROUPBOX),
+    ("BS_USERBUTTON", BS_USERBUTTON),
+    ("BS_AUTORADIOBUTTON", BS_AUTORADIOBUTTON),
+    ("BS_PUSHBOX", BS_PUSHBOX),
+    ("BS_OWNERDRAW", BS_OWNERDRAW),
+    ("BS_SPLITBUTTON", BS_SPLITBUTTON),
+    ("BS_DEFSPLITBUTTON", BS_DEFSPLITBUTTON),
+    ("BS_COMMANDLINK", BS_COMMANDLINK),
+    ("BS_DEFCOMMANDLINK", BS_DEFCOMMANDLINK),
+    ("BS_TYPEMASK", BS_TYPEMASK),
+    ("BS_LEFTTEXT", BS_LEFTTEXT),
+    ("BS_RIGHTBUTTON", BS_LEFTTEXT),
+    ("BS_TEXT", 0),
+    ("BS_ICON", BS_ICON),
+    ("BS_BITMAP", BS_BITMAP),
+    ("BS_LEFT", BS_LEFT),
+    ("BS_RIGHT", BS_RIGHT),
+    ("BS_CENTER", BS_CENTER),
+    ("BS_TOP", BS_TOP),
+    ("BS_BOTTOM", BS_BOTTOM),
+    ("BS_VCENTER", BS_VCENTER),
+    ("BS_PUSHLIKE", BS_PUSHLIKE),
+    ("BS_MULTILINE", BS_MULTILINE),
+    ("BS_NOTIFY", BS_NOTIFY),
+    ("BS_FLAT", BS_FLAT),
+    ("ES_LEFT", ES_LEFT),
+    ("ES_CENTER", ES_CENTER),
+    ("ES_RIGHT", ES_RIGHT),
+    ("ES_MULTILINE", ES_MULTILINE),
+    ("ES_UPPERCASE", ES_UPPERCASE),
+    ("ES_LOWERCASE", ES_LOWERCASE),
+    ("ES_PASSWORD", ES_PASSWORD),
+    ("ES_AUTOVSCROLL", ES_AUTOVSCROLL),
+    ("ES_AUTOHSCROLL", ES_AUTOHSCROLL),
+    ("ES_NOHIDESEL", ES_NOHIDESEL),
+    ("ES_OEMCONVERT", ES_OEMCONVERT),
+    ("ES_READONLY", ES_READONLY),
+    ("ES_WANTRETURN", ES_WANTRETURN),
+    ("ES_NUMBER", ES_NUMBER),
+    ("SS_LEFT", SS_LEFT),
+    ("SS_CENTER", SS_CENTER),
+    ("SS_RIGHT", SS_RIGHT),
+    ("SS_ICON", SS_ICON),
+    ("SS_BLACKRECT", SS_BLACKRECT),
+    ("SS_GRAYRECT", SS_GRAYRECT),
+    ("SS_WHITERECT", SS_WHITERECT),
+    ("SS_BLACKFRAME", SS_BLACKFRAME),
+    ("SS_GRAYFRAME", SS_GRAYFRAME),
+    ("SS_WHITEFRAME", SS_WHITEFRAME),
+    ("SS_USERITEM", SS_USERITEM),
+    ("SS_SIMPLE", SS_SIMPLE),
+    ("SS_LEFTNOWORDWRAP", SS_LEFTNOWORDWRAP),
+    ("SS_OWNERDRAW", SS_OWNERDRAW),
+    ("SS_BITMAP", SS_BITMAP),
+    ("SS_ENHMETAFILE", SS_ENHMETAFILE),
+    ("SS_ETCHEDHORZ", SS_ETCHEDHORZ),
+    ("SS_ETCHEDVERT", SS_ETCHEDVERT),
+    ("SS_ETCHEDFRAME", SS_ETCHEDFRAME),
+    ("SS_TYPEMASK", SS_TYPEMASK),
+    ("SS_REALSIZECONTROL", SS_REALSIZECONTROL),
+    ("SS_NOPREFIX", SS_NOPREFIX),
+    ("SS_NOTIFY", SS_NOTIFY),
+    ("SS_CENTERIMAGE", SS_CENTERIMAGE),
+    ("SS_RIGHTJUST", SS_RIGHTJUST),
+    ("SS_REALSIZEIMAGE", SS_REALSIZEIMAGE),
+    ("SS_SUNKEN", SS_SUNKEN),
+    ("SS_EDITCONTROL", SS_EDITCONTROL),
+    ("SS_ENDELLIPSIS", SS_ENDELLIPSIS),
+    ("SS_PATHELLIPSIS", SS_PATHELLIPSIS),
+    ("SS_WORDELLIPSIS", SS_WORDELLIPSIS),
+    ("CBS_SIMPLE", CBS_SIMPLE),
+    ("CBS_DROPDOWN", CBS_DROPDOWN),
+    ("CBS_DROPDOWNLIST", CBS_DROPDOWNLIST),
+    ("CBS_OWNERDRAWFIXED", CBS_OWNERDRAWFIXED),
+    ("CBS_OWNERDRAWVARIABLE", CBS_OWNERDRAWVARIABLE),
+    ("CBS_AUTOHSCROLL", CBS_AUTOHSCROLL),
+    ("CBS_OEMCONVERT", CBS_OEMCONVERT),
+    ("CBS_SORT", CBS_SORT),
+    ("CBS_HASSTRINGS", CBS_HASSTRINGS),
+    ("CBS_NOINTEGRALHEIGHT", CBS_NOINTEGRALHEIGHT),
+    ("CBS_DISABLENOSCROLL", CBS_DISABLENOSCROLL),
+    ("CBS_UPPERCASE", CBS_UPPERCASE),
+    ("CBS_LOWERCASE", CBS_LOWERCASE),
+    ("LBS_NOTIFY", LBS_NOTIFY),
+    ("LBS_SORT", LBS_SORT),
+    ("LBS_NOREDRAW", LBS_NOREDRAW),
+    ("LBS_MULTIPLESEL", LBS_MULTIPLESEL),
+    ("LBS_OWNERDRAWFIXED", LBS_OWNERDRAWFIXED),
+    ("LBS_OWNERDRAWVARIABLE", LBS_OWNERDRAWVARIABLE),
+    ("LBS_HASSTRINGS", LBS_HASSTRINGS),
+    ("LBS_USETABSTOPS", LBS_USETABSTOPS),
+    ("LBS_NOINTEGRALHEIGHT", LBS_NOINTEGRALHEIGHT),
+    ("LBS_MULTICOLUMN", LBS_MULTICOLUMN),
+    ("LBS_WANTKEYBOARDINPUT", LBS_WANTKEYBOARDINPUT),
+    ("LBS_EXTENDEDSEL", LBS_EXTENDEDSEL),
+    ("LBS_DISABLENOSCROLL", LBS_DISABLENOSCROLL),
+    ("LBS_NODATA", LBS_NODATA),
+    ("LBS_NOSEL", LBS_NOSEL),
+    ("LBS_COMBOBOX", LBS_COMBOBOX),
+    ("LBS_STANDARD", LBS_NOTIFY | LBS_SORT | WS_VSCROLL | WS_BORDER),
+    ("SBS_HORZ", SBS_HORZ),
+    ("SBS_VERT", SBS_VERT),
+    ("SBS_TOPALIGN", SBS_TOPALIGN),
+    ("SBS_LEFTALIGN", SBS_LEFTALIGN),
+    ("SBS_BOTTOMALIGN", SBS_BOTTOMALIGN),
+    ("SBS_RIGHTALIGN", SBS_RIGHTALIGN),
+    ("SBS_SIZEBOX", SBS_SIZEBOX),
+    ("SBS_SIZEGRIP", SBS_SIZEGRIP),
+    ("LVS_ICON", LVS_ICON),
+    ("LVS_REPORT", LVS_REPORT),
+    ("LVS_SMALLICON", LVS_SMALLICON),
+    ("LVS_LIST", LVS_LIST),
+    ("LVS_TYPEMASK", LVS_TYPEMASK),
+    ("LVS_SINGLESEL", LVS_SINGLESEL),
+    ("LVS_SHOWSELALWAYS", LVS_SHOWSELALWAYS),
+    ("LVS_SORTASCENDING", LVS_SORTASCENDING),
+    ("LVS_SORTDESCENDING", LVS_SORTDESCENDING),
+    ("LVS_SHAREIMAGELISTS", LVS_SHAREIMAGELISTS),
+    ("LVS_NOLABELWRAP", LVS_NOLABELWRAP),
+    ("LVS_AUTOARRANGE", LVS_AUTOARRANGE),
+    ("LVS_EDITLABELS", LVS_EDITLABELS),
+    ("LVS_OWNERDRAWFIXED", LVS_OWNERDRAWFIXED),
+    ("LVS_ALIGNLEFT", LVS_ALIGNLEFT),
+    ("LVS_OWNERDATA", LVS_OWNERDATA),
+    ("LVS_NOSCROLL", LVS_NOSCROLL),
+    ("LVS_NOCOLUMNHEADER", LVS_NOCOLUMNHEADER),
+    ("LVS_NOSORTHEADER", LVS_NOSORTHEADER),
+    ("TVS_HASBUTTONS", TVS_HASBUTTONS),
+    ("TVS_HASLINES", TVS_HASLINES),
+    ("TVS_LINESATROOT", TVS_LINESATROOT),
+    ("TVS_EDITLABELS", TVS_EDITLABELS),
+    ("TVS_DISABLEDRAGDROP", TVS_DISABLEDRAGDROP),
+    ("TVS_SHOWSELALWAYS", TVS_SHOWSELALWAYS),
+    ("TVS_RTLREADING", TVS_RTLREADING),
+    ("TVS_NOTOOLTIPS", TVS_NOTOOLTIPS),
+    ("TVS_CHECKBOXES", TVS_CHECKBOXES),
+    ("TVS_TRACKSELECT", TVS_TRACKSELECT),
+    ("TVS_SINGLEEXPAND", TVS_SINGLEEXPAND),
+    ("TVS_INFOTIP", TVS_INFOTIP),
+    ("TVS_FULLROWSELECT", TVS_FULLROWSELECT),
+    ("TVS_NOSCROLL", TVS_NOSCROLL),
+    ("TVS_NONEVENHEIGHT", TVS_NONEVENHEIGHT),
+    ("TVS_NOHSCROLL", TVS_NOHSCROLL),
+    ("TCS_SCROLLOPPOSITE", TCS_SCROLLOPPOSITE),
+    ("TCS_BOTTOM", TCS_BOTTOM),
+    ("TCS_RIGHT", TCS_RIGHT),
+    ("TCS_MULTISELECT", TCS_MULTISELECT),
+    ("TCS_FLATBUTTONS", TCS_FLATBUTTONS),
+    ("TCS_FORCEICONLEFT", TCS_FORCEICONLEFT),
+    ("TCS_FORCELABELLEFT", TCS_FORCELABELLEFT),
+    ("TCS_HOTTRACK", TCS_HOTTRACK),
+    ("TCS_VERTICAL", TCS_VERTICAL),
+    ("TCS_BUTTONS", TCS_BUTTONS),
+    ("TCS_MULTILINE", TCS_MULTILINE),
+    ("TCS_FIXEDWIDTH", TCS_FIXEDWIDTH),
+    ("TCS_RAGGEDRIGHT", TCS_RAGGEDRIGHT),
+    ("TCS_FOCUSONBUTTONDOWN", TCS_FOCUSONBUTTONDOWN),
+    ("TCS_OWNERDRAWFIXED", TCS_OWNERDRAWFIXED),
+    ("TCS_TOOLTIPS", TCS_TOOLTIPS),
+    ("TCS_FOCUSNEVER", TCS_FOCUSNEVER),
+    ("PBS_SMOOTH", PBS_SMOOTH),
+    ("PBS_VERTICAL", PBS_VERTICAL),
+    ("PBS_MARQUEE", PBS_MARQUEE),
+    ("PBS_SMOOTHREVERSE", PBS_SMOOTHREVERSE),
+    ("TBS_AUTOTICKS", TBS_AUTOTICKS),
+    ("TBS_VERT", TBS_VERT),
+    ("TBS_HORZ", TBS_HORZ),
+    ("TBS_TOP", TBS_TOP),
+    ("TBS_BOTTOM", TBS_BOTTOM),
+    ("TBS_LEFT", TBS_LEFT),
+    ("TBS_RIGHT", TBS_RIGHT),
+    ("TBS_BOTH", TBS_BOTH),
+    ("TBS_NOTICKS", TBS_NOTICKS),
+    ("TBS_ENABLESELRANGE", TBS_ENABLESELRANGE),
+    ("TBS_FIXEDLENGTH", TBS_FIXEDLENGTH),
+    ("TBS_NOTHUMB", TBS_NOTHUMB),
+    ("TBS_TOOLTIPS", TBS_TOOLTIPS),
+    ("TBS_REVERSED", TBS_REVERSED),
+    ("TBS_DOWNISLEFT", TBS_DOWNISLEFT),
+    ("TBS_NOTIFYBEFOREMOVE", TBS_NOTIFYBEFOREMOVE),
+    ("TBS_TRANSPARENTBKGND", TBS_TRANSPARENTBKGND),
+    ("UDS_WRAP", UDS_WRAP),
+    ("UDS_SETBUDDYINT", UDS_SETBUDDYINT),
+    ("UDS_ALIGNRIGHT", UDS_ALIGNRIGHT),
+    ("UDS_ALIGNLEFT", UDS_ALIGNLEFT),
+    ("UDS_AUTOBUDDY", UDS_AUTOBUDDY),
+    ("UDS_ARROWKEYS", UDS_ARROWKEYS),
+    ("UDS_HORZ", UDS_HORZ),
+    ("UDS_NOTHOUSANDS", UDS_NOTHOUSANDS),
+    ("UDS_HOTTRACK", UDS_HOTTRACK),
+    ("DTS_UPDOWN", DTS_UPDOWN),
+    ("DTS_SHOWNONE", DTS_SHOWNONE),
+    ("DTS_SHORTDATEFORMAT", DTS_SHORTDATEFORMAT),
+    ("DTS_LONGDATEFORMAT", DTS_LONGDATEFORMAT),
+    ("DTS_SHORTDATECENTURYFORMAT", DTS_SHORTDATECENTURYFORMAT),
+    ("DTS_TIMEFORMAT", DTS_TIMEFORMAT),
+    ("DTS_APPCANPARSE", DTS_APPCANPARSE),
+    ("DTS_RIGHTALIGN", DTS_RIGHTALIGN),
+    ("MCS_DAYSTATE", MCS_DAYSTATE),
+    ("MCS_MULTISELECT", MCS_MULTISELECT),
+    ("MCS_WEEKNUMBERS", MCS_WEEKNUMBERS),
+    ("MCS_NOTODAYCIRCLE", MCS_NOTODAYCIRCLE),
+    ("MCS_NOTODAY", MCS_NOTODAY),
+    ("MCS_NOTRAILINGDATES", MCS_NOTRAILINGDATES),
+    ("MCS_SHORTDAYSOFWEEK", MCS_SHORTDAYSOFWEEK),
+    ("MCS_NOSELCHANGEONNAV", MCS_NOSELCHANGEONNAV),
+    ("LWS_TRANSPARENT", LWS_TRANSPARENT),
+    ("LWS_IGNORERETURN", LWS_IGNORERETURN),
+    ("LWS_NOPREFIX", LWS_NOPREFIX),
+    ("LWS_USEVISUALSTYLE", LWS_USEVISUALSTYLE),
+    ("LWS_USECUSTOMTEXT", LWS_USECUSTOMTEXT),
+    ("LWS_RIGHT", LWS_RIGHT),
+];
+
+/// Style constants known without any header, for the symbol table.
+pub(crate) fn constants() -> impl Iterator<Item = (&'static str, i64)> {
+    STYLE_CONSTANTS.iter().map(|(name, value)| (*name, *value as i64))
+}
+
+/// The style a control statement gives before its own style argument is
+/// applied. CONTROL only implies WS_CHILD | WS_VISIBLE. COMBOBOX gets
+/// CBS_SIMPLE only when its style names no other type.
+pub fn default_control_style(kind: &str) -> u32 {
+    let style = match kind {
+        "LTEXT" => SS_LEFT | WS_GROUP,
+        "CTEXT" => SS_CENTER | WS_GROUP,
+        "RTEXT" => SS_RIGHT | WS_GROUP,
+        "ICON" => SS_ICON,
+        "PUSHBUTTON" => BS_PUSHBUTTON | WS_TABSTOP,
+        "DEFPUSHBUTTON" => BS_DEFPUSHBUTTON | WS_TABSTOP,
+        "PUSHBOX" => BS_PUSHBOX | WS_TABSTOP,
+        "CHECKBOX" => BS_CHECKBOX | WS_TABSTOP,
+        "AUTOCHECKBOX" => BS_AUTOCHECKBOX | WS_TABSTOP,
+        "RADIOBUTTON" => BS_RADIOBUTTON,
+        "AUTORADIOBUTTON" => BS_AUTORADIOBUTTON,
+        "STATE3" => BS_3STATE | WS_TABSTOP,
+        "AUTO3STATE" => BS_AUTO3STATE | WS_TABSTOP,
+        "GROUPBOX" => BS_GROUPBOX,
+        "EDITTEXT" => ES_LEFT | WS_BORDER | WS_TABSTOP,
+        "COMBOBOX" => WS_TABSTOP,
+        "LISTBOX" => LBS_NOTIFY | WS_BORDER,
+        "SCROLLBAR" => SBS_HORZ,
+        _ => 0,
+    };
+    style | WS_CHILD | WS_VISIBLE
+}
+
+/// The style a control is created with: the defaults of its statement
+/// keyword with the written style applied.
+pub(crate) fn control_style(
+    kind: &str,
+    style: Option<&Expr>,
+    symbols: &SymbolTable,
+    span: Span,
+    diagnostics: &mut Diagnostics,
+) -> u32 {
+    let mut value = default_control_style(kind);
+    if let Some(style) = style {
+        value = apply_style(value, style, symbols, span, diagnostics);
+    }
+    if kind == "COMBOBOX" && value & CBS_DROPDOWNLIST == 0 {
+        value |= CBS_SIMPLE;
+    }
+    value
+}
+
+/// The style a dialog is created with. Without a STYLE statement rc.exe
+/// uses WS_POPUP | WS_BORDER | WS_SYSMENU; CAPTION adds WS_CAPTION and FONT
+/// adds DS_SETFONT.
+pub(crate) fn dialog_style(
+    style: Option<&Expr>,
+    caption: bool,
+    font: bool,
+    symbols: &SymbolTable,
+    span: Span,
+    diagnostics: &mut Diagnostics,
+) -> u32 {
+    let mut value = match style {
+        Some(style) => apply_style(0, style, symbols, span, diagnostics),
+        None => WS_POPUP | WS_BORDER | WS_SYSMENU,
+    };
+    if caption {
+        value |= WS_CAPTION;
+    }
+    if font {
+        value |= DS_SETFONT;
+    }
+    value
+}
+
+/// Applies a style expression to `initial` the way rc.exe does: the terms of
+/// a `|` chain are added in turn and `NOT x` clears the bits of `x`. Symbols
+/// without a value are reported and ignored.
+pub(crate) fn apply_style(
+    initial: u32,
+    expr: &Expr,
+    symbols: &SymbolTable,
+    span: Span,
+    diagnostics: &mut Diagnostics,
+) -> u32 {
+    match expr {
+        Expr::Binary(BinaryOp::Or, lhs, rhs) => {
+            let style = apply_style(initial, lhs, symbols, span, diagnostics);
+            apply_style(style, rhs, symbols, span, diagnostics)
+        }
+        Expr::Unary(UnaryOp::Not, e) => match symbols.eval(e) {
+            Some(value) => initial & !(value as u32),
+            None => {
+                diagnostics.warning(Some(span), format!("unknown style '{}'", e));
+                initial
+            }
+        },
+        _ => match symbols.eval(expr) {
+            Some(value) => initial | value as u32,
+            None => {
+                diagnostics.warning(Some(span), format!("unknown style '{}'", expr));
+                initial
+            }
+        },
+    }
+}
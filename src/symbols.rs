@@ -0,0 +1,310 @@
+use crate::ast::{BinaryOp, Expr, UnaryOp};
+use crate::diagnostics::{Diagnostics, Span};
+use crate::preprocessor::{evaluate_strict, parse_number, Macro};
+use crate::resource::RcId;
+use crate::styles;
+use std::collections::HashMap;
+
+// Dialog result and command IDs from winuser.h.
+const WINUSER_IDS: &[(&str, i64)] = &[
+    ("IDOK", 1),
+    ("IDCANCEL", 2),
+    ("IDABORT", 3),
+    ("IDRETRY", 4),
+    ("IDIGNORE", 5),
+    ("IDYES", 6),
+    ("IDNO", 7),
+    ("IDCLOSE", 8),
+    ("IDHELP", 9),
+    ("IDTRYAGAIN", 10),
+    ("IDCONTINUE", 11),
+    ("WM_USER", 0x0400),
+    ("WM_APP", 0x8000),
+];
+
+// Common LANGUAGE arguments from winnt.h.
+const WINNT_LANGS: &[(&str, i64)] = &[
+    ("LANG_NEUTRAL", 0x00),
+    ("LANG_CHINESE", 0x04),
+    ("LANG_GERMAN", 0x07),
+    ("LANG_ENGLISH", 0x09),
+    ("LANG_SPANISH", 0x0a),
+    ("LANG_FRENCH", 0x0c),
+    ("LANG_ITALIAN", 0x10),
+    ("LANG_JAPANESE", 0x11),
+    ("LANG_KOREAN", 0x12),
+    ("LANG_DUTCH", 0x13),
+    ("LANG_POLISH", 0x15),
+    ("LANG_PORTUGUESE", 0x16),
+    ("LANG_RUSSIAN", 0x19),
+    ("SUBLANG_NEUTRAL", 0x00),
+    ("SUBLANG_DEFAULT", 0x01),
+    ("SUBLANG_SYS_DEFAULT", 0x02),
+    ("SUBLANG_ENGLISH_US", 0x01),
+    ("SUBLANG_ENGLISH_UK", 0x02),
+    ("SUBLANG_FRENCH", 0x01),
+    ("SUBLANG_GERMAN", 0x01),
+    ("SUBLANG_CHINESE_SIMPLIFIED", 0x02),
+];
+
+// Standard command and control IDs from afxres.h.
+const AFXRES_IDS: &[(&str, i64)] = &[
+    ("IDC_STATIC", -1),
+    ("ID_SEPARATOR", 0),
+    ("ID_APPLY_NOW", 0x3021),
+    ("ID_WIZBACK", 0x3023),
+    ("ID_WIZNEXT", 0x3024),
+    ("ID_WIZFINISH", 0x3025),
+    ("ID_FILE_NEW", 0xE100),
+    ("ID_FILE_OPEN", 0xE101),
+    ("ID_FILE_CLOSE", 0xE102),
+    ("ID_FILE_SAVE", 0xE103),
+    ("ID_FILE_SAVE_AS", 0xE104),
+    ("ID_FILE_PAGE_SETUP", 0xE105),
+    ("ID_FILE_PRINT_SETUP", 0xE106),
+    ("ID_FILE_PRINT", 0xE107),
+    ("ID_FILE_PRINT_DIRECT", 0xE108),
+    ("ID_FILE_PRINT_PREVIEW", 0xE109),
+    ("ID_FILE_MRU_FILE1", 0xE110),
+    ("ID_EDIT_CLEAR", 0xE120),
+    ("ID_EDIT_CLEAR_ALL", 0xE121),
+    ("ID_EDIT_COPY", 0xE122),
+    ("ID_EDIT_CUT", 0xE123),
+    ("ID_EDIT_FIND", 0xE124),
+    ("ID_EDIT_PASTE", 0xE125),
+    ("ID_EDIT_PASTE_LINK", 0xE126),
+    ("ID_EDIT_PASTE_SPECIAL", 0xE127),
+    ("ID_EDIT_REPEAT", 0xE128),
+    ("ID_EDIT_REPLACE", 0xE129),
+    ("ID_EDIT_SELECT_ALL", 0xE12A),
+    ("ID_EDIT_UNDO", 0xE12B),
+    ("ID_EDIT_REDO", 0xE12C),
+    ("ID_WINDOW_NEW", 0xE130),
+    ("ID_WINDOW_ARRANGE", 0xE131),
+    ("ID_WINDOW_CASCADE", 0xE132),
+    ("ID_WINDOW_TILE_HORZ", 0xE133),
+    ("ID_WINDOW_TILE_VERT", 0xE134),
+    ("ID_WINDOW_SPLIT", 0xE135),
+    ("ID_APP_ABOUT", 0xE140),
+    ("ID_APP_EXIT", 0xE141),
+    ("ID_HELP_INDEX", 0xE142),
+    ("ID_HELP_FINDER", 0xE143),
+    ("ID_HELP_USING", 0xE144),
+    ("ID_CONTEXT_HELP", 0xE145),
+    ("ID_HELP", 0xE146),
+    ("ID_DEFAULT_HELP", 0xE147),
+    ("ID_NEXT_PANE", 0xE150),
+    ("ID_PREV_PANE", 0xE151),
+    ("ID_VIEW_TOOLBAR", 0xE800),
+    ("ID_VIEW_STATUS_BAR", 0xE801),
+    ("AFX_IDS_APP_TITLE", 0xE000),
+    ("AFX_IDS_IDLEMESSAGE", 0xE001),
+    ("AFX_IDS_HELPMODEMESSAGE", 0xE002),
+];
+
+/// Numeric values of the symbols a script can use as IDs: the `#define`s seen
+/// by the preprocessor (usually from resource.h) on top of the built-in
+/// winuser.h, winnt.h and afxres.h constants and the window styles.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    values: HashMap<String, i64>,
+}
+
+impl SymbolTable {
+    pub fn with_builtins() -> Self {
+        let mut values = HashMap::new();
+        for (name, value) in WINUSER_IDS.iter().chain(WINNT_LANGS).chain(AFXRES_IDS) {
+            values.insert(name.to_string(), *value);
+        }
+        for (name, value) in styles::constants() {
+            values.insert(name.to_string(), value);
+        }
+        SymbolTable { values }
+    }
+
+    /// Builds the table from preprocessor macros. Macros that do not evaluate
+    /// to an integer (strings, empty guards, function-like macros) are skipped.
+    pub fn from_macros(macros: &HashMap<String, Macro>) -> Self {
+        let mut table = SymbolTable::with_builtins();
+
+        // Built-ins are visible to macro bodies such as `(WM_USER + 1)`.
+        let mut scope = macros.clone();
+        for (name, value) in &table.values {
+            scope.entry(name.clone()).or_insert_with(|| Macro {
+                params: None,
+                body: value.to_string(),
+            });
+        }
+
+        for (name, m) in macros {
+            if m.params.is_some() || m.body.is_empty() {
+                continue;
+            }
+            if let Ok(value) = evaluate_strict(&m.body, &scope) {
+                table.values.insert(name.clone(), value);
+            }
+        }
+        table
+    }
+
+    pub fn get(&self, name: &str) -> Option<i64> {
+        self.values.get(name).copied()
+    }
+
+    /// Evaluates an RC expression. Strings and unknown symbols have no value.
+    pub fn eval(&self, expr: &Expr) -> Option<i64> {
+        match expr {
+            Expr::Number(n) => Some(*n),
+            Expr::Symbol(name) => self.get(name),
+            Expr::String(_) => None,
+            Expr::Unary(UnaryOp::Neg, e) => self.eval(e).map(i64::wrapping_neg),
+            Expr::Unary(UnaryOp::BitNot | UnaryOp::Not, e) => self.eval(e).map(|v| !v),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (self.eval(lhs)?, self.eval(rhs)?);
+                match op {
+                    BinaryOp::Or => Some(lhs | rhs),
+                    BinaryOp::And => Some(lhs & rhs),
+                    BinaryOp::Add => Some(lhs.wrapping_add(rhs)),
+                    BinaryOp::Sub => Some(lhs.wrapping_sub(rhs)),
+                    BinaryOp::Mul => Some(lhs.wrapping_mul(rhs)),
+                    BinaryOp::Div => lhs.checked_div(rhs),
+                }
+            }
+        }
+    }
+
+    /// Resolves an ID as written in the script: a number or a symbol.
+    /// Unresolved symbols are reported and kept by name only.
+    pub fn resolve_id(&self, name: &str, span: Span, diagnostics: &mut Diagnostics) -> RcId {
+        let value = parse_number(name).or_else(|| self.get(name));
+        if value.is_none() && !name.is_empty() && !name.starts_with('"') {
+            diagnostics.warning(
+                Some(span),
+                format!("ID '{}' is not defined in any included header", name),
+            );
+        }
+        RcId {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    /// Like `resolve_id`, for an ID written as an expression such as `-1`
+    /// or `IDC_FIRST + 2`.
+    pub fn resolve_expr(&self, expr: &Expr, span: Span, diagnostics: &mut Diagnostics) -> RcId {
+        match expr {
+            Expr::Symbol(name) => self.resolve_id(name, span, diagnostics),
+            _ => RcId {
+                name: expr.to_string(),
+                value: self.eval(expr),
+            },
+        }
+    }
+}
+
+/// Warns about IDs of the same kind that share a numeric value under different names.
+pub fn check_collisions<'a>(
+    kind: &str,
+    ids: impl IntoIterator<Item = (&'a RcId, Span)>,
+    diagnostics: &mut Diagnostics,
+) {
+    let mut seen: HashMap<i64, &str> = HashMap::new();
+    for (id, span) in ids {
+        let Some(value) = id.value else { continue };
+        match seen.get(&value) {
+            Some(other) if *other != id.name => diagnostics.warning(
+                Some(span),
+                format!("{} IDs '{}' and '{}' both resolve to {}", kind, other, id.name, value),
+            ),
+            Some(_) => {}
+            None => {
+                seen.insert(value, &id.name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn macros(defines: &[(&str, &str)]) -> HashMap<String, Macro> {
+        defines
+            .iter()
+            .map(|(name, body)| {
+                (
+                    name.to_string(),
+                    Macro {
+                        params: None,
+                        body: body.to_string(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn table(defines: &[(&str, &str)]) -> SymbolTable {
+        SymbolTable::from_macros(&macros(defines))
+    }
+
+    #[test]
+    fn macros_resolve_through_each_other_and_the_builtins() {
+        let symbols = table(&[
+            ("IDD_MAIN", "100"),
+            ("IDC_FIRST", "(IDD_MAIN + 0x10)"),
+            ("IDC_USER", "(WM_USER + 1)"),
+            ("IDC_LONG", "42L"),
+        ]);
+        assert_eq!(symbols.get("IDD_MAIN"), Some(100));
+        assert_eq!(symbols.get("IDC_FIRST"), Some(116));
+        assert_eq!(symbols.get("IDC_USER"), Some(0x401));
+        assert_eq!(symbols.get("IDC_LONG"), Some(42));
+        assert_eq!(symbols.get("IDOK"), Some(1));
+        assert_eq!(symbols.get("IDC_STATIC"), Some(-1));
+    }
+
+    #[test]
+    fn non_integer_macros_have_no_value() {
+        let mut defines = macros(&[("APP_NAME", "\"My App\""), ("GUARD", "")]);
+        defines.insert(
+            "MAKE".into(),
+            Macro {
+                params: Some(Vec::new()),
+                body: "1".into(),
+            },
+        );
+        let symbols = SymbolTable::from_macros(&defines);
+        assert_eq!(symbols.get("APP_NAME"), None);
+        assert_eq!(symbols.get("GUARD"), None);
+        assert_eq!(symbols.get("MAKE"), None);
+    }
+
+    #[test]
+    fn ids_keep_their_name() {
+        let symbols = table(&[("IDD_MAIN", "100")]);
+        let mut diagnostics = Diagnostics::new();
+        let id = symbols.resolve_id("IDD_MAIN", Span::default(), &mut diagnostics);
+        assert_eq!((id.name.as_str(), id.value), ("IDD_MAIN", Some(100)));
+        let id = symbols.resolve_id("0x10", Span::default(), &mut diagnostics);
+        assert_eq!(id.value, Some(16));
+        assert!(diagnostics.render().is_empty());
+
+        let id = symbols.resolve_id("IDD_MISSING", Span::default(), &mut diagnostics);
+        assert_eq!(id.value, None);
+        assert!(diagnostics.render().contains("'IDD_MISSING' is not defined"));
+    }
+
+    #[test]
+    fn colliding_ids_are_reported() {
+        let ids = [
+            RcId { name: "IDC_A".into(), value: Some(1000) },
+            RcId { name: "IDC_A".into(), value: Some(1000) },
+            RcId { name: "IDC_B".into(), value: Some(1000) },
+            RcId { name: "IDC_C".into(), value: None },
+        ];
+        let mut diagnostics = Diagnostics::new();
+        check_collisions("control", ids.iter().map(|id| (id, Span::default())), &mut diagnostics);
+        assert_eq!(diagnostics.count(crate::diagnostics::Severity::Warning), 1);
+        assert!(diagnostics.render().contains("control IDs 'IDC_A' and 'IDC_B' both resolve to 1000"));
+    }
+}
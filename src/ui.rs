@@ -0,0 +1,347 @@
+use crate::error::Result;
+use std::io::Write;
+use xml::writer::{EmitterConfig, EventWriter, XmlEvent};
+
+/// A Qt Designer form: the top-level widget and the sections that follow it.
+#[derive(Debug, Clone, Default)]
+pub struct UiForm {
+    /// Name of the class generated by uic.
+    pub class: String,
+    pub widget: UiWidget,
+    /// Promoted widget classes used by the form.
+    pub custom_widgets: Vec<UiCustomWidget>,
+    /// Names of the widgets in keyboard focus order.
+    pub tab_stops: Vec<String>,
+    /// Locations of the .qrc files the form's pixmaps come from.
+    pub resources: Vec<String>,
+    /// Signal and slot connections made by the generated `setupUi`.
+    pub connections: Vec<UiConnection>,
+    /// Names of the button groups that widgets join through their
+    /// `buttonGroup` attribute.
+    pub button_groups: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UiWidget {
+    pub class: String,
+    pub name: String,
+    pub properties: Vec<UiProperty>,
+    /// Settings Designer keeps outside the widget's properties, such as
+    /// `headerVisible` of item views.
+    pub attributes: Vec<UiProperty>,
+    /// Texts of the items of a combo box or list widget.
+    pub items: Vec<String>,
+    /// The layout arranging the widget's children; widgets in it have no
+    /// geometry of their own.
+    pub layout: Option<UiLayout>,
+    /// Children placed by their geometry.
+    pub children: Vec<UiWidget>,
+    /// Written as an XML comment before the widget, for notes that the
+    /// developer porting the form should read.
+    pub comment: Option<String>,
+}
+
+/// A class a widget is promoted to: Designer shows the base class and uic
+/// includes `header` for the real one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UiCustomWidget {
+    pub class: String,
+    pub extends: String,
+    pub header: String,
+}
+
+/// A connection from a signal of `sender` to a slot of `receiver`, both
+/// given by object name, with signatures such as `accepted()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UiConnection {
+    pub sender: String,
+    pub signal: String,
+    pub receiver: String,
+    pub slot: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UiLayout {
+    /// `QGridLayout`, `QFormLayout`, `QHBoxLayout` or `QVBoxLayout`.
+    pub class: String,
+    pub name: String,
+    pub items: Vec<UiLayoutItem>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UiLayoutItem {
+    /// Row, column, row span and column span in a grid or form layout.
+    pub cell: Option<(usize, usize, usize, usize)>,
+    pub content: UiLayoutContent,
+}
+
+#[derive(Debug, Clone)]
+pub enum UiLayoutContent {
+    Widget(UiWidget),
+    Layout(UiLayout),
+    Spacer(UiSpacer),
+}
+
+/// Empty space that grows in one direction, from its size hint.
+#[derive(Debug, Clone)]
+pub struct UiSpacer {
+    pub name: String,
+    pub horizontal: bool,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UiProperty {
+    pub name: String,
+    pub value: UiValue,
+}
+
+/// Property values, written as the matching Designer element.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiValue {
+    String(String),
+    /// Untranslated text such as the object name of a label's buddy.
+    CString(String),
+    Bool(bool),
+    Number(i64),
+    /// A single enumerator such as `Qt::AlignLeft`.
+    Enum(String),
+    /// Flags joined with `|`.
+    Set(String),
+    Rect { x: i32, y: i32, width: i32, height: i32 },
+    Size { width: i32, height: i32 },
+    /// An image from a .qrc file, by its `:/` path.
+    Pixmap { resource: String, path: String },
+}
+
+impl UiWidget {
+    pub fn new(class: &str, name: &str) -> Self {
+        UiWidget {
+            class: class.to_string(),
+            name: name.to_string(),
+            ..UiWidget::default()
+        }
+    }
+
+    /// Sets a property, replacing any earlier value.
+    pub fn set(&mut self, name: &str, value: UiValue) {
+        match self.properties.iter_mut().find(|p| p.name == name) {
+            Some(property) => property.value = value,
+            None => self.properties.push(UiProperty {
+                name: name.to_string(),
+                value,
+            }),
+        }
+    }
+
+    /// Sets an attribute, replacing any earlier value.
+    pub fn set_attribute(&mut self, name: &str, value: UiValue) {
+        match self.attributes.iter_mut().find(|p| p.name == name) {
+            Some(attribute) => attribute.value = value,
+            None => self.attributes.push(UiProperty {
+                name: name.to_string(),
+                value,
+            }),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&UiValue> {
+        self.properties.iter().find(|p| p.name == name).map(|p| &p.value)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut UiValue> {
+        self.properties.iter_mut().find(|p| p.name == name).map(|p| &mut p.value)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<UiValue> {
+        let index = self.properties.iter().position(|p| p.name == name)?;
+        Some(self.properties.remove(index).value)
+    }
+}
+
+/// Writes `form` as a version 4.0 .ui document.
+pub fn write_form<W: Write>(form: &UiForm, out: W) -> Result<()> {
+    let mut writer = EmitterConfig::new()
+        .perform_indent(true)
+        .create_writer(out);
+
+    writer.write(XmlEvent::start_element("ui").attr("version", "4.0"))?;
+    write_text_element(&mut writer, "class", &form.class)?;
+    write_widget(&mut writer, &form.widget)?;
+    if !form.custom_widgets.is_empty() {
+        writer.write(XmlEvent::start_element("customwidgets"))?;
+        for custom in &form.custom_widgets {
+            writer.write(XmlEvent::start_element("customwidget"))?;
+            write_text_element(&mut writer, "class", &custom.class)?;
+            write_text_element(&mut writer, "extends", &custom.extends)?;
+            write_text_element(&mut writer, "header", &custom.header)?;
+            writer.write(XmlEvent::end_element())?; // customwidget
+        }
+        writer.write(XmlEvent::end_element())?; // customwidgets
+    }
+    if !form.tab_stops.is_empty() {
+        writer.write(XmlEvent::start_element("tabstops"))?;
+        for name in &form.tab_stops {
+            write_text_element(&mut writer, "tabstop", name)?;
+        }
+        writer.write(XmlEvent::end_element())?; // tabstops
+    }
+    writer.write(XmlEvent::start_element("resources"))?;
+    for location in &form.resources {
+        writer.write(XmlEvent::start_element("include").attr("location", location))?;
+        writer.write(XmlEvent::end_element())?; // include
+    }
+    writer.write(XmlEvent::end_element())?; // resources
+    writer.write(XmlEvent::start_element("connections"))?;
+    for connection in &form.connections {
+        writer.write(XmlEvent::start_element("connection"))?;
+        write_text_element(&mut writer, "sender", &connection.sender)?;
+        write_text_element(&mut writer, "signal", &connection.signal)?;
+        write_text_element(&mut writer, "receiver", &connection.receiver)?;
+        write_text_element(&mut writer, "slot", &connection.slot)?;
+        writer.write(XmlEvent::end_element())?; // connection
+    }
+    writer.write(XmlEvent::end_element())?; // connections
+    if !form.button_groups.is_empty() {
+        writer.write(XmlEvent::start_element("buttongroups"))?;
+        for name in &form.button_groups {
+            writer.write(XmlEvent::start_element("buttongroup").attr("name", name))?;
+            writer.write(XmlEvent::end_element())?; // buttongroup
+        }
+        writer.write(XmlEvent::end_element())?; // buttongroups
+    }
+    writer.write(XmlEvent::end_element())?; // ui
+    Ok(())
+}
+
+fn write_widget<W: Write>(writer: &mut EventWriter<W>, widget: &UiWidget) -> Result<()> {
+    if let Some(comment) = &widget.comment {
+        writer.write(XmlEvent::comment(&format!(" {} ", comment.replace("--", "- -"))))?;
+    }
+    writer.write(
+        XmlEvent::start_element("widget")
+            .attr("class", &widget.class)
+            .attr("name", &widget.name),
+    )?;
+    for property in &widget.properties {
+        writer.write(XmlEvent::start_element("property").attr("name", &property.name))?;
+        write_value(writer, &property.value)?;
+        writer.write(XmlEvent::end_element())?; // property
+    }
+    for attribute in &widget.attributes {
+        writer.write(XmlEvent::start_element("attribute").attr("name", &attribute.name))?;
+        write_value(writer, &attribute.value)?;
+        writer.write(XmlEvent::end_element())?; // attribute
+    }
+    for item in &widget.items {
+        writer.write(XmlEvent::start_element("item"))?;
+        writer.write(XmlEvent::start_element("property").attr("name", "text"))?;
+        write_text_element(writer, "string", item)?;
+        writer.write(XmlEvent::end_element())?; // property
+        writer.write(XmlEvent::end_element())?; // item
+    }
+    if let Some(layout) = &widget.layout {
+        write_layout(writer, layout)?;
+    }
+    for child in &widget.children {
+        write_widget(writer, child)?;
+    }
+    writer.write(XmlEvent::end_element())?; // widget
+    Ok(())
+}
+
+fn write_layout<W: Write>(writer: &mut EventWriter<W>, layout: &UiLayout) -> Result<()> {
+    writer.write(
+        XmlEvent::start_element("layout")
+            .attr("class", &layout.class)
+            .attr("name", &layout.name),
+    )?;
+    for item in &layout.items {
+        let cell = item.cell.map(|(row, column, row_span, column_span)| {
+            (row.to_string(), column.to_string(), row_span.to_string(), column_span.to_string())
+        });
+        let mut start = XmlEvent::start_element("item");
+        if let Some((row, column, row_span, column_span)) = &cell {
+            start = start.attr("row", row).attr("column", column);
+            if row_span != "1" {
+                start = start.attr("rowspan", row_span);
+            }
+            if column_span != "1" {
+                start = start.attr("colspan", column_span);
+            }
+        }
+        writer.write(start)?;
+        match &item.content {
+            UiLayoutContent::Widget(widget) => write_widget(writer, widget)?,
+            UiLayoutContent::Layout(layout) => write_layout(writer, layout)?,
+            UiLayoutContent::Spacer(spacer) => {
+                writer.write(XmlEvent::start_element("spacer").attr("name", &spacer.name))?;
+                writer.write(XmlEvent::start_element("property").attr("name", "orientation"))?;
+                let orientation = if spacer.horizontal { "Qt::Horizontal" } else { "Qt::Vertical" };
+                write_value(writer, &UiValue::Enum(orientation.into()))?;
+                writer.write(XmlEvent::end_element())?; // property
+                writer.write(
+                    XmlEvent::start_element("property")
+                        .attr("name", "sizeHint")
+                        .attr("stdset", "0"),
+                )?;
+                write_value(
+                    writer,
+                    &UiValue::Size {
+                        width: spacer.width,
+                        height: spacer.height,
+                    },
+                )?;
+                writer.write(XmlEvent::end_element())?; // property
+                writer.write(XmlEvent::end_element())?; // spacer
+            }
+        }
+        writer.write(XmlEvent::end_element())?; // item
+    }
+    writer.write(XmlEvent::end_element())?; // layout
+    Ok(())
+}
+
+fn write_value<W: Write>(writer: &mut EventWriter<W>, value: &UiValue) -> Result<()> {
+    match value {
+        UiValue::String(text) => write_text_element(writer, "string", text)?,
+        UiValue::CString(text) => write_text_element(writer, "cstring", text)?,
+        UiValue::Bool(b) => write_text_element(writer, "bool", if *b { "true" } else { "false" })?,
+        UiValue::Number(n) => write_text_element(writer, "number", &n.to_string())?,
+        UiValue::Enum(name) => write_text_element(writer, "enum", name)?,
+        UiValue::Set(flags) => write_text_element(writer, "set", flags)?,
+        UiValue::Rect {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            writer.write(XmlEvent::start_element("rect"))?;
+            write_text_element(writer, "x", &x.to_string())?;
+            write_text_element(writer, "y", &y.to_string())?;
+            write_text_element(writer, "width", &width.to_string())?;
+            write_text_element(writer, "height", &height.to_string())?;
+            writer.write(XmlEvent::end_element())?; // rect
+        }
+        UiValue::Size { width, height } => {
+            writer.write(XmlEvent::start_element("size"))?;
+            write_text_element(writer, "width", &width.to_string())?;
+            write_text_element(writer, "height", &height.to_string())?;
+            writer.write(XmlEvent::end_element())?; // size
+        }
+        UiValue::Pixmap { resource, path } => {
+            writer.write(XmlEvent::start_element("pixmap").attr("resource", resource))?;
+            writer.write(XmlEvent::characters(path))?;
+            writer.write(XmlEvent::end_element())?; // pixmap
+        }
+    }
+    Ok(())
+}
+
+fn write_text_element<W: Write>(writer: &mut EventWriter<W>, name: &str, text: &str) -> Result<()> {
+    writer.write(XmlEvent::start_element(name))?;
+    writer.write(XmlEvent::characters(text))?;
+    writer.write(XmlEvent::end_element())?;
+    Ok(())
+}
@@ -0,0 +1,1435 @@
+use crate::dialog_units::BaseUnits;
+use crate::form::FormContext;
+use crate::generator::{qrc_path, QRC_FILE_NAME};
+use crate::resource::{DialogControl, DialogInitEntry, RcResourceData};
+use crate::styles::*;
+use crate::ui::{UiCustomWidget, UiValue, UiWidget};
+use log::info;
+
+// Dialog unit height of a combo box selection field and of a list item,
+// which is one line of the dialog font.
+const COMBO_FIELD_HEIGHT: i32 = 14;
+const LIST_ITEM_HEIGHT: i32 = 8;
+
+// Dialog units an up-down may be away from the edit it attaches to.
+const BUDDY_GAP: i32 = 2;
+
+// Pixels taken by a list box border on each side.
+const LIST_BORDER: i32 = 2;
+
+// The date and time picker format bits; DTS_TIMEFORMAT includes DTS_UPDOWN.
+const DTS_FORMATMASK: u32 = 0xC;
+
+// DLGINIT messages: items added to combo and list boxes, and the settings
+// of MFC feature pack controls, written as `<Class_Name>value</Class_Name>`.
+const CB_ADDSTRING: u32 = 0x143;
+const LB_ADDSTRING: u32 = 0x180;
+const WM_MFC_INITCTRL: u32 = 0x37C;
+// The 16-bit CB_ADDSTRING that resource editors write in DLGINIT data.
+const CB_ADDSTRING_16: u32 = 0x403;
+
+type ControlMapper = fn(&DialogControl, &mut FormContext) -> UiWidget;
+
+// Window classes and the functions converting their controls. Class names
+// are compared ignoring case. The commctrl.h and richedit.h class name
+// macros are listed too, for scripts that use them without the headers.
+const CONTROL_CLASSES: &[(&str, ControlMapper)] = &[
+    ("Button", button_widget),
+    ("Edit", edit_widget),
+    ("Static", static_widget),
+    ("ComboBox", combo_box_widget),
+    ("ListBox", list_box_widget),
+    ("ScrollBar", scroll_bar_widget),
+    ("SysListView32", list_view_widget),
+    ("WC_LISTVIEW", list_view_widget),
+    ("SysTreeView32", tree_view_widget),
+    ("WC_TREEVIEW", tree_view_widget),
+    ("SysTabControl32", tab_widget),
+    ("WC_TABCONTROL", tab_widget),
+    ("msctls_progress32", progress_bar_widget),
+    ("PROGRESS_CLASS", progress_bar_widget),
+    ("msctls_trackbar32", slider_widget),
+    ("TRACKBAR_CLASS", slider_widget),
+    ("msctls_updown32", spin_box_widget),
+    ("UPDOWN_CLASS", spin_box_widget),
+    ("SysDateTimePick32", date_time_widget),
+    ("DATETIMEPICK_CLASS", date_time_widget),
+    ("SysMonthCal32", calendar_widget),
+    ("MONTHCAL_CLASS", calendar_widget),
+    ("SysIPAddress32", ip_address_widget),
+    ("WC_IPADDRESS", ip_address_widget),
+    ("msctls_hotkey32", hot_key_widget),
+    ("HOTKEY_CLASS", hot_key_widget),
+    ("SysLink", link_widget),
+    ("WC_LINK", link_widget),
+    ("RichEdit", rich_edit_widget),
+    ("RichEdit20A", rich_edit_widget),
+    ("RichEdit20W", rich_edit_widget),
+    ("RICHEDIT_CLASS", rich_edit_widget),
+    ("RichEdit50W", rich_edit_widget),
+    ("MSFTEDIT_CLASS", rich_edit_widget),
+    ("MfcButton", mfc_button_widget),
+    ("MfcColorButton", mfc_color_button_widget),
+    ("MfcEditBrowse", mfc_edit_browse_widget),
+    ("MfcFontComboBox", mfc_font_combo_box_widget),
+    ("MfcLink", mfc_link_widget),
+    ("MfcMaskedEdit", mfc_masked_edit_widget),
+    ("MfcMenuButton", mfc_menu_button_widget),
+    ("MfcPropertyGrid", mfc_property_grid_widget),
+    ("MfcShellList", mfc_shell_list_widget),
+    ("MfcShellTree", mfc_shell_tree_widget),
+];
+
+pub(crate) fn control_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    match CONTROL_CLASSES
+        .iter()
+        .find(|(class, _)| class.eq_ignore_ascii_case(&control.class))
+    {
+        Some((_, mapper)) => mapper(control, cx),
+        None => custom_widget(control, cx),
+    }
+}
+
+// Widget classes derived from QFrame, which can draw the edges that
+// extended styles give a control.
+const FRAME_CLASSES: &[&str] = &[
+    "QFrame", "QLabel", "QListWidget", "QTreeWidget", "QTreeView", "QPlainTextEdit", "QTextEdit",
+];
+
+/// Properties from the window styles every control has: WS_DISABLED, the
+/// absence of WS_VISIBLE, client and static edges and right-to-left layout.
+pub(crate) fn set_window_styles(widget: &mut UiWidget, control: &DialogControl) {
+    let style = control.effective_style;
+    let ex_style = control.effective_ex_style;
+    if style & WS_DISABLED != 0 {
+        widget.set("enabled", UiValue::Bool(false));
+    }
+    if style & WS_VISIBLE == 0 {
+        widget.set("visible", UiValue::Bool(false));
+    }
+    if ex_style & (WS_EX_CLIENTEDGE | WS_EX_STATICEDGE) != 0 {
+        if widget.class == "QLineEdit" || widget.class == "QSpinBox" {
+            widget.remove("frame");
+        } else if FRAME_CLASSES.contains(&widget.class.as_str()) {
+            let shape = if ex_style & WS_EX_CLIENTEDGE != 0 {
+                "QFrame::StyledPanel"
+            } else {
+                "QFrame::Panel"
+            };
+            widget.set("frameShape", UiValue::Enum(shape.into()));
+            widget.set("frameShadow", UiValue::Enum("QFrame::Sunken".into()));
+        }
+    }
+    if ex_style & WS_EX_LAYOUTRTL != 0 {
+        widget.set("layoutDirection", UiValue::Enum("Qt::RightToLeft".into()));
+    }
+}
+
+// Controls of an application's own window class become a placeholder of
+// the same size, promoted to the class named by the promotion rule.
+fn custom_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let bordered = control.effective_style & WS_BORDER != 0;
+    let mut widget = new_widget(if bordered { "QFrame" } else { "QWidget" }, control, cx);
+    if bordered {
+        widget.set("frameShape", UiValue::Enum("QFrame::Box".into()));
+    }
+    let class = cx.promotion.class_name(&control.class);
+    info!("Promoting control {} of window class {} to {}", control.id, control.class, class);
+    promote(&mut widget, &class, cx);
+    widget.comment = Some(format!("TODO: port the \"{}\" window class", control.class.trim_matches('"')));
+    widget
+}
+
+// The DLGINIT entries of a control.
+fn init_entries<'a>(control: &DialogControl, cx: &FormContext<'a>) -> Vec<&'a DialogInitEntry> {
+    match (cx.init, control.id.value) {
+        (Some(init), Some(id)) => init.for_control(id).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Items a DLGINIT resource adds to a combo or list box.
+fn init_items(control: &DialogControl, cx: &FormContext) -> Vec<String> {
+    init_entries(control, cx)
+        .into_iter()
+        .filter(|entry| matches!(entry.message, CB_ADDSTRING | CB_ADDSTRING_16 | LB_ADDSTRING))
+        .map(DialogInitEntry::text)
+        .collect()
+}
+
+// The DLGINIT settings of an MFC control, by name without the class prefix:
+// `<MFCButton_TooltipText>Hi</MFCButton_TooltipText>` gives `TooltipText`.
+fn mfc_settings(control: &DialogControl, cx: &FormContext) -> Vec<(String, String)> {
+    let mut settings = Vec::new();
+    for entry in init_entries(control, cx) {
+        if entry.message != WM_MFC_INITCTRL {
+            continue;
+        }
+        let text = entry.text();
+        let mut rest = text.as_str();
+        while let Some(open) = rest.find('<') {
+            let Some(close) = rest[open..].find('>') else {
+                break;
+            };
+            let tag = &rest[open + 1..open + close];
+            let after = &rest[open + close + 1..];
+            let end_tag = format!("</{}>", tag);
+            let Some(end) = after.find(&end_tag) else {
+                break;
+            };
+            let name = tag.split_once('_').map_or(tag, |(_, name)| name);
+            settings.push((name.to_string(), after[..end].to_string()));
+            rest = &after[end + end_tag.len()..];
+        }
+    }
+    settings
+}
+
+fn setting<'a>(settings: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    settings
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+        .filter(|value| !value.is_empty())
+}
+
+// Shows `widget` in Designer as its current class but has uic create
+// `class` instead, declared in the header given by the promotion rule.
+fn promote(widget: &mut UiWidget, class: &str, cx: &mut FormContext) {
+    if !cx.custom_widgets.iter().any(|c| c.class == class) {
+        cx.custom_widgets.push(UiCustomWidget {
+            class: class.to_string(),
+            extends: widget.class.clone(),
+            header: cx.promotion.header(class),
+        });
+    }
+    widget.class = class.to_string();
+}
+
+// A widget of `class` named after the control, at the control's position.
+fn new_widget(class: &str, control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let mut widget = UiWidget::new(class, &cx.names.name_for(&control.id, class));
+    widget.set("geometry", geometry(control, cx.units));
+    widget
+}
+
+// BUTTON controls, chosen by their BS_* type. Push-like check boxes and
+// radio buttons become checkable push buttons.
+fn button_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let button_type = style & BS_TYPEMASK;
+    let push_like = style & BS_PUSHLIKE != 0;
+    let class = match button_type {
+        BS_CHECKBOX | BS_AUTOCHECKBOX | BS_3STATE | BS_AUTO3STATE if !push_like => "QCheckBox",
+        BS_RADIOBUTTON | BS_AUTORADIOBUTTON if !push_like => "QRadioButton",
+        BS_GROUPBOX => "QGroupBox",
+        BS_COMMANDLINK | BS_DEFCOMMANDLINK => "QCommandLinkButton",
+        BS_SPLITBUTTON | BS_DEFSPLITBUTTON => "QToolButton",
+        _ => "QPushButton",
+    };
+
+    let mut widget = new_widget(class, control, cx);
+    let text = UiValue::String(control.text.clone());
+    match class {
+        "QGroupBox" => {
+            widget.set("title", text);
+            if style & BS_FLAT != 0 {
+                widget.set("flat", UiValue::Bool(true));
+            }
+        }
+        "QCheckBox" | "QRadioButton" => {
+            widget.set("text", text);
+            if style & BS_LEFTTEXT != 0 {
+                widget.set("layoutDirection", UiValue::Enum("Qt::RightToLeft".into()));
+            }
+        }
+        "QToolButton" => {
+            widget.set("text", text);
+            widget.set("popupMode", UiValue::Enum("QToolButton::MenuButtonPopup".into()));
+            widget.set("toolButtonStyle", UiValue::Enum("Qt::ToolButtonTextOnly".into()));
+            if style & BS_FLAT != 0 {
+                widget.set("autoRaise", UiValue::Bool(true));
+            }
+        }
+        _ => {
+            widget.set("text", text);
+            if style & BS_FLAT != 0 {
+                widget.set("flat", UiValue::Bool(true));
+            }
+        }
+    }
+
+    match button_type {
+        BS_DEFPUSHBUTTON | BS_DEFCOMMANDLINK => {
+            widget.set("autoDefault", UiValue::Bool(true));
+            widget.set("default", UiValue::Bool(true));
+        }
+        BS_CHECKBOX | BS_AUTOCHECKBOX if push_like => widget.set("checkable", UiValue::Bool(true)),
+        BS_3STATE | BS_AUTO3STATE if push_like => widget.set("checkable", UiValue::Bool(true)),
+        BS_3STATE | BS_AUTO3STATE => widget.set("tristate", UiValue::Bool(true)),
+        BS_RADIOBUTTON | BS_AUTORADIOBUTTON if push_like => {
+            widget.set("checkable", UiValue::Bool(true));
+            widget.set("autoExclusive", UiValue::Bool(button_type == BS_AUTORADIOBUTTON));
+        }
+        // The application checks plain radio buttons itself.
+        BS_RADIOBUTTON => widget.set("autoExclusive", UiValue::Bool(false)),
+        _ => {}
+    }
+    widget
+}
+
+// EDIT controls. Input restrictions that Designer cannot express as a
+// validator are kept as input method hints.
+fn edit_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    if style & ES_MULTILINE != 0 {
+        let mut widget = new_widget("QPlainTextEdit", control, cx);
+        set_scroll_area(&mut widget, style);
+        // Without horizontal scrolling the edit wraps at its width.
+        if style & (WS_HSCROLL | ES_AUTOHSCROLL) != 0 {
+            widget.set("lineWrapMode", UiValue::Enum("QPlainTextEdit::NoWrap".into()));
+        }
+        if style & ES_READONLY != 0 {
+            widget.set("readOnly", UiValue::Bool(true));
+        }
+        if !control.text.is_empty() {
+            widget.set("plainText", UiValue::String(control.text.clone()));
+        }
+        return widget;
+    }
+
+    let mut widget = new_widget("QLineEdit", control, cx);
+    if !control.text.is_empty() {
+        widget.set("text", UiValue::String(control.text.clone()));
+    }
+    if style & WS_BORDER == 0 {
+        widget.set("frame", UiValue::Bool(false));
+    }
+    if style & ES_PASSWORD != 0 {
+        widget.set("echoMode", UiValue::Enum("QLineEdit::Password".into()));
+    }
+    if style & ES_READONLY != 0 {
+        widget.set("readOnly", UiValue::Bool(true));
+    }
+    match style & (ES_CENTER | ES_RIGHT) {
+        ES_CENTER => widget.set("alignment", UiValue::Set("Qt::AlignHCenter|Qt::AlignVCenter".into())),
+        ES_RIGHT => widget.set("alignment", UiValue::Set("Qt::AlignRight|Qt::AlignVCenter".into())),
+        _ => {}
+    }
+
+    let mut hints = Vec::new();
+    if style & ES_NUMBER != 0 {
+        hints.push("Qt::ImhDigitsOnly");
+    }
+    if style & ES_UPPERCASE != 0 {
+        hints.push("Qt::ImhUppercaseOnly");
+    }
+    if style & ES_LOWERCASE != 0 {
+        hints.push("Qt::ImhLowercaseOnly");
+    }
+    if !hints.is_empty() {
+        widget.set("inputMethodHints", UiValue::Set(hints.join("|")));
+    }
+    widget
+}
+
+// STATIC controls: text labels, frames and lines, and images.
+fn static_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let mut widget = match style & SS_TYPEMASK {
+        SS_ICON => image_label(control, "ICON", cx),
+        SS_BITMAP => image_label(control, "BITMAP", cx),
+        SS_ETCHEDHORZ | SS_ETCHEDVERT => {
+            // Designer's own class for QFrame lines.
+            let mut line = new_widget("Line", control, cx);
+            let vertical = style & SS_TYPEMASK == SS_ETCHEDVERT;
+            line.set("orientation", UiValue::Enum(orientation(vertical).into()));
+            return line;
+        }
+        SS_ETCHEDFRAME => frame(control, "QFrame::Box", "QFrame::Sunken", cx),
+        SS_BLACKFRAME | SS_GRAYFRAME | SS_WHITEFRAME => frame(control, "QFrame::Box", "QFrame::Plain", cx),
+        SS_BLACKRECT | SS_GRAYRECT | SS_WHITERECT => frame(control, "QFrame::Panel", "QFrame::Plain", cx),
+        SS_OWNERDRAW | SS_USERITEM | SS_ENHMETAFILE => frame(control, "QFrame::NoFrame", "QFrame::Plain", cx),
+        _ => text_label(control, cx),
+    };
+    if style & SS_SUNKEN != 0 {
+        if widget.class == "QLabel" {
+            widget.set("frameShape", UiValue::Enum("QFrame::Panel".into()));
+        }
+        widget.set("frameShadow", UiValue::Enum("QFrame::Sunken".into()));
+    }
+    widget
+}
+
+fn text_label(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let mut widget = new_widget("QLabel", control, cx);
+
+    // Win32 statics underline the character after `&` unless SS_NOPREFIX
+    // is set, QLabel only does so for labels with a buddy.
+    let text = if style & SS_NOPREFIX != 0 {
+        control.text.clone()
+    } else {
+        strip_mnemonic(&control.text)
+    };
+    if text.contains('<') {
+        widget.set("textFormat", UiValue::Enum("Qt::PlainText".into()));
+    }
+    widget.set("text", UiValue::String(text));
+
+    // Static text is drawn from the top unless SS_CENTERIMAGE is set.
+    let vertical = if style & SS_CENTERIMAGE != 0 {
+        "Qt::AlignVCenter"
+    } else {
+        "Qt::AlignTop"
+    };
+    let (horizontal, wrap) = match style & SS_TYPEMASK {
+        SS_CENTER => ("Qt::AlignHCenter", true),
+        SS_RIGHT => ("Qt::AlignRight", true),
+        SS_LEFTNOWORDWRAP | SS_SIMPLE => ("Qt::AlignLeft", false),
+        _ => ("Qt::AlignLeft", true),
+    };
+    widget.set("alignment", UiValue::Set(format!("{}|{}", horizontal, vertical)));
+    if wrap {
+        widget.set("wordWrap", UiValue::Bool(true));
+    }
+    widget
+}
+
+// A label showing the icon or bitmap resource named by the control text.
+fn image_label(control: &DialogControl, type_name: &str, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let mut widget = new_widget("QLabel", control, cx);
+    let name = control.text.trim_matches('"');
+    let file = cx
+        .script
+        .get(type_name, name)
+        .or_else(|| cx.script.get(type_name, &format!("\"{}\"", name)))
+        .and_then(|resource| match &resource.data {
+            RcResourceData::Bitmap(bitmap) => Some(&bitmap.file),
+            RcResourceData::Icon(icon) => Some(&icon.file),
+            _ => None,
+        });
+    match file {
+        Some(file) => {
+            widget.set(
+                "pixmap",
+                UiValue::Pixmap {
+                    resource: QRC_FILE_NAME.to_string(),
+                    path: format!(":/{}", qrc_path(file)),
+                },
+            );
+            cx.uses_resources = true;
+        }
+        None => cx.diagnostics.warning(
+            Some(control.span),
+            format!("no {} resource '{}' for the image control", type_name, name),
+        ),
+    }
+    if style & SS_REALSIZECONTROL != 0 {
+        widget.set("scaledContents", UiValue::Bool(true));
+    }
+    if style & SS_CENTERIMAGE != 0 {
+        widget.set("alignment", UiValue::Set("Qt::AlignCenter".into()));
+    }
+    widget
+}
+
+fn frame(control: &DialogControl, shape: &str, shadow: &str, cx: &mut FormContext) -> UiWidget {
+    let mut widget = new_widget("QFrame", control, cx);
+    widget.set("frameShape", UiValue::Enum(shape.into()));
+    widget.set("frameShadow", UiValue::Enum(shadow.into()));
+    widget
+}
+
+// The character following a single `&`, which Windows underlines.
+pub(crate) fn mnemonic(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '&' {
+            match chars.next() {
+                Some('&') => {}
+                next => return next,
+            }
+        }
+    }
+    None
+}
+
+// Removes the `&` mnemonic markers of a Win32 caption; `&&` is a literal `&`.
+fn strip_mnemonic(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '&' {
+            if chars.peek() == Some(&'&') {
+                out.push('&');
+                chars.next();
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// COMBOBOX controls. The height given in the script includes the drop-down
+// list, so the widget gets the height of the selection field and the rest
+// becomes the number of visible items.
+fn combo_box_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let mut widget = new_widget("QComboBox", control, cx);
+    let field_height = cx.units.to_pixels_y(COMBO_FIELD_HEIGHT.min(control.height));
+    if let Some(UiValue::Rect { height, .. }) = widget.get_mut("geometry") {
+        *height = field_height;
+    }
+    if style & CBS_DROPDOWNLIST != CBS_DROPDOWNLIST {
+        widget.set("editable", UiValue::Bool(true));
+    }
+    if style & CBS_SORT != 0 {
+        widget.set("insertPolicy", UiValue::Enum("QComboBox::InsertAlphabetically".into()));
+    }
+    widget.items = init_items(control, cx);
+    let items = (control.height - COMBO_FIELD_HEIGHT) / LIST_ITEM_HEIGHT;
+    widget.set("maxVisibleItems", UiValue::Number(items.max(1) as i64));
+    widget
+}
+
+// LISTBOX controls. Without LBS_NOINTEGRALHEIGHT Windows shrinks the list to
+// show only whole items, and so does the converted widget.
+fn list_box_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let mut widget = new_widget("QListWidget", control, cx);
+    if style & LBS_NOINTEGRALHEIGHT == 0 {
+        let item_height = cx.units.to_pixels_y(LIST_ITEM_HEIGHT);
+        let border = if style & WS_BORDER != 0 { 2 * LIST_BORDER } else { 0 };
+        if let Some(UiValue::Rect { height, .. }) = widget.get_mut("geometry") {
+            let items = ((*height - border) / item_height).max(1);
+            *height = items * item_height + border;
+        }
+    }
+    if style & WS_BORDER == 0 {
+        widget.set("frameShape", UiValue::Enum("QFrame::NoFrame".into()));
+    }
+    if style & LBS_DISABLENOSCROLL != 0 {
+        widget.set("verticalScrollBarPolicy", UiValue::Enum("Qt::ScrollBarAlwaysOn".into()));
+    } else if style & WS_VSCROLL == 0 {
+        widget.set("verticalScrollBarPolicy", UiValue::Enum("Qt::ScrollBarAlwaysOff".into()));
+    }
+    if style & WS_HSCROLL == 0 {
+        widget.set("horizontalScrollBarPolicy", UiValue::Enum("Qt::ScrollBarAlwaysOff".into()));
+    }
+
+    let selection = if style & LBS_NOSEL != 0 {
+        Some("QAbstractItemView::NoSelection")
+    } else if style & LBS_EXTENDEDSEL != 0 {
+        Some("QAbstractItemView::ExtendedSelection")
+    } else if style & LBS_MULTIPLESEL != 0 {
+        Some("QAbstractItemView::MultiSelection")
+    } else {
+        None
+    };
+    if let Some(selection) = selection {
+        widget.set("selectionMode", UiValue::Enum(selection.into()));
+    }
+    if style & LBS_MULTICOLUMN != 0 {
+        widget.set("flow", UiValue::Enum("QListView::TopToBottom".into()));
+        widget.set("isWrapping", UiValue::Bool(true));
+    }
+    if style & LBS_SORT != 0 {
+        widget.set("sortingEnabled", UiValue::Bool(true));
+    }
+    widget.items = init_items(control, cx);
+    widget
+}
+
+// SCROLLBAR controls. Size boxes and grips are left as scroll bars.
+fn scroll_bar_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let mut widget = new_widget("QScrollBar", control, cx);
+    widget.set("orientation", UiValue::Enum(orientation(control.effective_style & SBS_VERT != 0).into()));
+    widget
+}
+
+// List views in report mode become a tree widget showing a flat table,
+// the other views a list widget.
+fn list_view_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let view = style & LVS_TYPEMASK;
+    let mut widget = if view == LVS_REPORT {
+        let mut widget = new_widget("QTreeWidget", control, cx);
+        widget.set("rootIsDecorated", UiValue::Bool(false));
+        widget.set("itemsExpandable", UiValue::Bool(false));
+        if style & LVS_NOCOLUMNHEADER != 0 {
+            widget.set_attribute("headerVisible", UiValue::Bool(false));
+        }
+        widget
+    } else {
+        let mut widget = new_widget("QListWidget", control, cx);
+        match view {
+            LVS_ICON => widget.set("viewMode", UiValue::Enum("QListView::IconMode".into())),
+            LVS_SMALLICON => widget.set("isWrapping", UiValue::Bool(true)),
+            _ => {
+                widget.set("flow", UiValue::Enum("QListView::TopToBottom".into()));
+                widget.set("isWrapping", UiValue::Bool(true));
+            }
+        }
+        widget
+    };
+    set_scroll_area(&mut widget, style | WS_VSCROLL | WS_HSCROLL);
+    if style & LVS_NOSCROLL != 0 {
+        widget.set("verticalScrollBarPolicy", UiValue::Enum("Qt::ScrollBarAlwaysOff".into()));
+        widget.set("horizontalScrollBarPolicy", UiValue::Enum("Qt::ScrollBarAlwaysOff".into()));
+    }
+    set_edit_triggers(&mut widget, style & LVS_EDITLABELS != 0);
+    let selection = if style & LVS_SINGLESEL != 0 {
+        "QAbstractItemView::SingleSelection"
+    } else {
+        "QAbstractItemView::ExtendedSelection"
+    };
+    widget.set("selectionMode", UiValue::Enum(selection.into()));
+    if style & (LVS_SORTASCENDING | LVS_SORTDESCENDING) != 0 {
+        widget.set("sortingEnabled", UiValue::Bool(true));
+    }
+    widget
+}
+
+fn tree_view_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let mut widget = new_widget("QTreeWidget", control, cx);
+    set_scroll_area(&mut widget, style | WS_VSCROLL | WS_HSCROLL);
+    set_edit_triggers(&mut widget, style & TVS_EDITLABELS != 0);
+    if style & TVS_LINESATROOT == 0 {
+        widget.set("rootIsDecorated", UiValue::Bool(false));
+    }
+    widget.set_attribute("headerVisible", UiValue::Bool(false));
+    widget
+}
+
+// The pages of a Win32 tab control are separate dialogs or sibling
+// controls, so the tab widget is left without pages.
+fn tab_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let mut widget = new_widget("QTabWidget", control, cx);
+    let position = match (style & TCS_VERTICAL != 0, style & TCS_BOTTOM != 0) {
+        (false, false) => None,
+        (false, true) => Some("QTabWidget::South"),
+        (true, false) => Some("QTabWidget::West"),
+        (true, true) => Some("QTabWidget::East"),
+    };
+    if let Some(position) = position {
+        widget.set("tabPosition", UiValue::Enum(position.into()));
+    }
+    if style & (TCS_BUTTONS | TCS_FLATBUTTONS) != 0 {
+        widget.set("documentMode", UiValue::Bool(true));
+    }
+    widget
+}
+
+fn progress_bar_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let mut widget = new_widget("QProgressBar", control, cx);
+    if style & PBS_MARQUEE != 0 {
+        // A zero range makes the bar show activity instead of progress.
+        widget.set("maximum", UiValue::Number(0));
+    }
+    widget.set("value", UiValue::Number(0));
+    widget.set("textVisible", UiValue::Bool(false));
+    if style & PBS_VERTICAL != 0 {
+        widget.set("orientation", UiValue::Enum("Qt::Vertical".into()));
+    }
+    widget
+}
+
+// Trackbars. Without TBS_AUTOTICKS Windows only marks both ends of the range.
+fn slider_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let vertical = style & TBS_VERT != 0;
+    let mut widget = new_widget("QSlider", control, cx);
+    widget.set("maximum", UiValue::Number(100));
+    widget.set("orientation", UiValue::Enum(orientation(vertical).into()));
+    if style & TBS_NOTICKS == 0 {
+        let position = match (style & TBS_BOTH != 0, style & TBS_TOP != 0, vertical) {
+            (true, _, _) => "QSlider::TicksBothSides",
+            (false, true, false) => "QSlider::TicksAbove",
+            (false, false, false) => "QSlider::TicksBelow",
+            (false, true, true) => "QSlider::TicksLeft",
+            (false, false, true) => "QSlider::TicksRight",
+        };
+        widget.set("tickPosition", UiValue::Enum(position.into()));
+        let interval = if style & TBS_AUTOTICKS != 0 { 1 } else { 100 };
+        widget.set("tickInterval", UiValue::Number(interval));
+    }
+    if style & TBS_REVERSED != 0 {
+        widget.set("invertedAppearance", UiValue::Bool(true));
+    }
+    widget
+}
+
+// A lone up-down control; one attached to an edit is merged with it.
+fn spin_box_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let mut widget = new_widget("QSpinBox", control, cx);
+    set_up_down(&mut widget, control.effective_style);
+    widget
+}
+
+/// Whether `up_down` is the up-down control that attaches itself to `edit`:
+/// it has UDS_AUTOBUDDY, comes right after the edit in z-order and is either
+/// aligned to the edit by Windows or placed against it.
+pub(crate) fn is_spin_buddy(edit: &DialogControl, up_down: &DialogControl) -> bool {
+    let style = up_down.effective_style;
+    if !edit.class.eq_ignore_ascii_case("Edit")
+        || !(up_down.class.eq_ignore_ascii_case("msctls_updown32")
+            || up_down.class.eq_ignore_ascii_case("UPDOWN_CLASS"))
+        || style & UDS_AUTOBUDDY == 0
+    {
+        return false;
+    }
+    if style & (UDS_ALIGNLEFT | UDS_ALIGNRIGHT) != 0 {
+        return true;
+    }
+    let near = |a: i32, a_len: i32, b: i32, b_len: i32| a <= b + b_len + BUDDY_GAP && b <= a + a_len + BUDDY_GAP;
+    near(edit.x, edit.width, up_down.x, up_down.width) && near(edit.y, edit.height, up_down.y, up_down.height)
+}
+
+/// One QSpinBox for an edit and its up-down buddy. Windows moves an aligned
+/// up-down into the edit's area; otherwise the spin box covers both.
+pub(crate) fn buddy_spin_box(edit: &DialogControl, up_down: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = up_down.effective_style;
+    let area = if style & (UDS_ALIGNLEFT | UDS_ALIGNRIGHT) != 0 {
+        edit.clone()
+    } else {
+        let x = edit.x.min(up_down.x);
+        let y = edit.y.min(up_down.y);
+        DialogControl {
+            x,
+            y,
+            width: (edit.x + edit.width).max(up_down.x + up_down.width) - x,
+            height: (edit.y + edit.height).max(up_down.y + up_down.height) - y,
+            ..edit.clone()
+        }
+    };
+    let mut widget = new_widget("QSpinBox", &area, cx);
+    set_up_down(&mut widget, style);
+
+    let edit_style = edit.effective_style;
+    match edit_style & (ES_CENTER | ES_RIGHT) {
+        ES_CENTER => widget.set("alignment", UiValue::Set("Qt::AlignHCenter|Qt::AlignVCenter".into())),
+        ES_RIGHT => widget.set("alignment", UiValue::Set("Qt::AlignRight|Qt::AlignVCenter".into())),
+        _ => {}
+    }
+    if edit_style & ES_READONLY != 0 {
+        widget.set("readOnly", UiValue::Bool(true));
+    }
+    if edit_style & WS_BORDER == 0 {
+        widget.set("frame", UiValue::Bool(false));
+    }
+    // A QSpinBox always steps with the arrow keys, which a Win32 up-down
+    // only does with UDS_ARROWKEYS.
+    if style & UDS_ARROWKEYS == 0 {
+        widget.comment = Some("TODO: the up-down did not step with the arrow keys (no UDS_ARROWKEYS); QSpinBox does".into());
+    }
+    widget
+}
+
+// Properties a QSpinBox takes from up-down styles. Windows up-downs count
+// from 0 to 100 unless the application sets a range.
+fn set_up_down(widget: &mut UiWidget, style: u32) {
+    widget.set("maximum", UiValue::Number(100));
+    if style & UDS_WRAP != 0 {
+        widget.set("wrapping", UiValue::Bool(true));
+    }
+    if style & UDS_SETBUDDYINT != 0 && style & UDS_NOTHOUSANDS == 0 {
+        widget.set("groupSeparatorShown", UiValue::Bool(true));
+    }
+}
+
+fn date_time_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    if style & DTS_FORMATMASK == DTS_TIMEFORMAT & DTS_FORMATMASK {
+        return new_widget("QTimeEdit", control, cx);
+    }
+    let mut widget = new_widget("QDateEdit", control, cx);
+    if style & DTS_UPDOWN == 0 {
+        widget.set("calendarPopup", UiValue::Bool(true));
+    }
+    widget
+}
+
+fn calendar_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let mut widget = new_widget("QCalendarWidget", control, cx);
+    if style & MCS_WEEKNUMBERS == 0 {
+        widget.set(
+            "verticalHeaderFormat",
+            UiValue::Enum("QCalendarWidget::NoVerticalHeader".into()),
+        );
+    }
+    widget
+}
+
+fn ip_address_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let mut widget = new_widget("QLineEdit", control, cx);
+    widget.set("inputMask", UiValue::String("000.000.000.000;_".into()));
+    widget
+}
+
+fn hot_key_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    new_widget("QKeySequenceEdit", control, cx)
+}
+
+// SysLink text is already HTML-like markup; anchors without a target
+// still need one to be clickable in a QLabel.
+fn link_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let mut widget = new_widget("QLabel", control, cx);
+    let text = control.text.replace("<a>", "<a href=\"#\">").replace("<A>", "<a href=\"#\">");
+    widget.set("text", UiValue::String(text));
+    widget.set("textFormat", UiValue::Enum("Qt::RichText".into()));
+    widget.set("wordWrap", UiValue::Bool(true));
+    widget.set(
+        "textInteractionFlags",
+        UiValue::Set("Qt::LinksAccessibleByKeyboard|Qt::LinksAccessibleByMouse".into()),
+    );
+    if style & LWS_RIGHT != 0 {
+        widget.set("alignment", UiValue::Set("Qt::AlignRight|Qt::AlignTop".into()));
+    }
+    widget
+}
+
+fn rich_edit_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let style = control.effective_style;
+    let mut widget = new_widget("QTextEdit", control, cx);
+    set_scroll_area(&mut widget, style);
+    if style & ES_READONLY != 0 {
+        widget.set("readOnly", UiValue::Bool(true));
+    }
+    widget
+}
+
+// MFC feature pack controls (CMFCButton and friends). Those without a Qt
+// counterpart are promoted placeholders with a note for the developer.
+fn mfc_button_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let settings = mfc_settings(control, cx);
+    let mut widget = button_widget(control, cx);
+    if let Some(tooltip) = setting(&settings, "TooltipText") {
+        widget.set("toolTip", UiValue::String(tooltip.to_string()));
+    }
+    widget
+}
+
+fn mfc_color_button_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let mut widget = new_widget("QToolButton", control, cx);
+    widget.set("text", UiValue::String(control.text.clone()));
+    widget.set("toolButtonStyle", UiValue::Enum("Qt::ToolButtonTextOnly".into()));
+    promote(&mut widget, "ColorButton", cx);
+    widget.comment = Some("TODO: port CMFCColorButton; ColorButton should pick a colour with QColorDialog".into());
+    widget
+}
+
+fn mfc_edit_browse_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let settings = mfc_settings(control, cx);
+    let mut widget = edit_widget(control, cx);
+    let dialog = match setting(&settings, "Mode") {
+        Some("2") => "QFileDialog::getExistingDirectory",
+        _ => "QFileDialog::getOpenFileName",
+    };
+    widget.comment = Some(format!(
+        "TODO: port CMFCEditBrowseCtrl; add a browse button calling {}",
+        dialog
+    ));
+    widget
+}
+
+fn mfc_font_combo_box_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let settings = mfc_settings(control, cx);
+    let mut widget = new_widget("QFontComboBox", control, cx);
+    let field_height = cx.units.to_pixels_y(COMBO_FIELD_HEIGHT.min(control.height));
+    if let Some(UiValue::Rect { height, .. }) = widget.get_mut("geometry") {
+        *height = field_height;
+    }
+    let true_type = setting(&settings, "ShowTrueTypeFonts") != Some("FALSE");
+    let raster = setting(&settings, "ShowRasterTypeFonts") == Some("TRUE");
+    if true_type && !raster {
+        widget.set("fontFilters", UiValue::Set("QFontComboBox::ScalableFonts".into()));
+    }
+    widget
+}
+
+// CMFCLinkCtrl opens its URL itself; without one it opens its caption.
+fn mfc_link_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let settings = mfc_settings(control, cx);
+    let mut widget = new_widget("QLabel", control, cx);
+    let text = strip_mnemonic(&control.text);
+    let url = format!(
+        "{}{}",
+        setting(&settings, "UrlPrefix").unwrap_or(""),
+        setting(&settings, "Url").unwrap_or(&text)
+    );
+    let link = format!("<a href=\"{}\">{}</a>", escape_html(&url), escape_html(&text));
+    widget.set("text", UiValue::String(link));
+    widget.set("textFormat", UiValue::Enum("Qt::RichText".into()));
+    widget.set("openExternalLinks", UiValue::Bool(true));
+    if let Some(tooltip) = setting(&settings, "Tooltip") {
+        widget.set("toolTip", UiValue::String(tooltip.to_string()));
+    }
+    widget
+}
+
+// Escapes text for a rich text label, quotes included so that it can go in
+// an attribute.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// CMFCMaskedEdit pairs a mask with an input template of the same length in
+// which `_` marks the editable positions.
+fn mfc_masked_edit_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let settings = mfc_settings(control, cx);
+    let mut widget = edit_widget(control, cx);
+    let (Some(mask), Some(template)) = (setting(&settings, "Mask"), setting(&settings, "InputTemplate")) else {
+        return widget;
+    };
+    let mut input_mask = String::new();
+    for (m, t) in mask.chars().zip(template.chars()) {
+        if t != '_' {
+            if "AaNnXx90Dd#HhBb>!<[]{}\\".contains(t) {
+                input_mask.push('\\');
+            }
+            input_mask.push(t);
+            continue;
+        }
+        input_mask.push(match m {
+            'D' => '9',
+            'd' => '0',
+            '+' => '#',
+            'C' => 'A',
+            'c' => 'a',
+            'A' => 'N',
+            'a' => 'n',
+            _ => 'x',
+        });
+    }
+    widget.set("inputMask", UiValue::String(input_mask));
+    widget
+}
+
+fn mfc_menu_button_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let mut widget = new_widget("QToolButton", control, cx);
+    widget.set("text", UiValue::String(control.text.clone()));
+    widget.set("popupMode", UiValue::Enum("QToolButton::InstantPopup".into()));
+    widget.set("toolButtonStyle", UiValue::Enum("Qt::ToolButtonTextOnly".into()));
+    widget.comment = Some("TODO: port CMFCMenuButton; attach its menu with QToolButton::setMenu".into());
+    widget
+}
+
+fn mfc_property_grid_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let settings = mfc_settings(control, cx);
+    let mut widget = new_widget("QTreeWidget", control, cx);
+    if setting(&settings, "HeaderCtrl") == Some("FALSE") {
+        widget.set_attribute("headerVisible", UiValue::Bool(false));
+    }
+    promote(&mut widget, "PropertyGrid", cx);
+    widget.comment = Some("TODO: port CMFCPropertyGridCtrl; PropertyGrid shows name and value columns".into());
+    widget
+}
+
+fn mfc_shell_list_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let mut widget = new_widget("QTreeView", control, cx);
+    widget.set("rootIsDecorated", UiValue::Bool(false));
+    widget.comment = Some("TODO: port CMFCShellListCtrl; give the view a QFileSystemModel".into());
+    widget
+}
+
+fn mfc_shell_tree_widget(control: &DialogControl, cx: &mut FormContext) -> UiWidget {
+    let mut widget = new_widget("QTreeView", control, cx);
+    widget.set_attribute("headerVisible", UiValue::Bool(false));
+    widget.comment = Some("TODO: port CMFCShellTreeCtrl; give the view a QFileSystemModel".into());
+    widget
+}
+
+// Frame and scroll bar policies of widgets derived from QAbstractScrollArea.
+fn set_scroll_area(widget: &mut UiWidget, style: u32) {
+    if style & WS_BORDER == 0 {
+        widget.set("frameShape", UiValue::Enum("QFrame::NoFrame".into()));
+    }
+    if style & WS_VSCROLL == 0 {
+        widget.set("verticalScrollBarPolicy", UiValue::Enum("Qt::ScrollBarAlwaysOff".into()));
+    }
+    if style & WS_HSCROLL == 0 {
+        widget.set("horizontalScrollBarPolicy", UiValue::Enum("Qt::ScrollBarAlwaysOff".into()));
+    }
+}
+
+// Item views only edit labels when the control allows it.
+fn set_edit_triggers(widget: &mut UiWidget, editable: bool) {
+    let triggers = if editable {
+        "QAbstractItemView::EditKeyPressed|QAbstractItemView::SelectedClicked"
+    } else {
+        "QAbstractItemView::NoEditTriggers"
+    };
+    widget.set("editTriggers", UiValue::Set(triggers.into()));
+}
+
+fn orientation(vertical: bool) -> &'static str {
+    if vertical {
+        "Qt::Vertical"
+    } else {
+        "Qt::Horizontal"
+    }
+}
+
+// The control rectangle in pixels. Edges are converted rather than sizes so
+// that adjacent controls stay adjacent after rounding.
+fn geometry(control: &DialogControl, units: BaseUnits) -> UiValue {
+    let x = units.to_pixels_x(control.x);
+    let y = units.to_pixels_y(control.y);
+    UiValue::Rect {
+        x,
+        y,
+        width: units.to_pixels_x(control.x + control.width) - x,
+        height: units.to_pixels_y(control.y + control.height) - y,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::form::tests::{convert, form, widget};
+    use crate::form::{FormOptions, PromotionRule};
+    use crate::ui::{UiForm, UiValue};
+
+    fn property(form: &UiForm, name: &str, property: &str) -> Option<UiValue> {
+        widget(form, name).get(property).cloned()
+    }
+
+    fn string(text: &str) -> Option<UiValue> {
+        Some(UiValue::String(text.into()))
+    }
+
+    fn enumerator(name: &str) -> Option<UiValue> {
+        Some(UiValue::Enum(name.into()))
+    }
+
+    const TRUE: Option<UiValue> = Some(UiValue::Bool(true));
+    const FALSE: Option<UiValue> = Some(UiValue::Bool(false));
+
+    #[test]
+    fn buttons_by_type() {
+        let form = form(
+            "PUSHBUTTON \"&Run\", IDC_RUN, 5, 5, 50, 14, BS_FLAT\n\
+             DEFPUSHBUTTON \"Go\", IDC_GO, 60, 5, 50, 14\n\
+             AUTOCHECKBOX \"Check\", IDC_CHECK, 5, 25, 50, 10, BS_LEFTTEXT\n\
+             AUTO3STATE \"Three\", IDC_THREE, 60, 25, 50, 10\n\
+             AUTOCHECKBOX \"Toggle\", IDC_TOGGLE, 115, 25, 50, 14, BS_PUSHLIKE\n\
+             RADIOBUTTON \"Manual\", IDC_MANUAL, 5, 40, 50, 10\n\
+             GROUPBOX \"Options\", IDC_OPTIONS, 5, 55, 100, 40\n\
+             CONTROL \"Link\", IDC_LINK, \"Button\", BS_DEFCOMMANDLINK, 120, 55, 70, 20\n\
+             CONTROL \"Split\", IDC_SPLIT, \"Button\", BS_SPLITBUTTON | BS_FLAT, 120, 80, 70, 14",
+        );
+        assert_eq!(widget(&form, "run").class, "QPushButton");
+        assert_eq!(property(&form, "run", "text"), string("&Run"));
+        assert_eq!(property(&form, "run", "flat"), TRUE);
+        assert_eq!(property(&form, "go", "default"), TRUE);
+        assert_eq!(property(&form, "go", "autoDefault"), TRUE);
+
+        assert_eq!(widget(&form, "check").class, "QCheckBox");
+        assert_eq!(property(&form, "check", "layoutDirection"), enumerator("Qt::RightToLeft"));
+        assert_eq!(widget(&form, "three").class, "QCheckBox");
+        assert_eq!(property(&form, "three", "tristate"), TRUE);
+        assert_eq!(widget(&form, "toggle").class, "QPushButton");
+        assert_eq!(property(&form, "toggle", "checkable"), TRUE);
+
+        assert_eq!(widget(&form, "manual").class, "QRadioButton");
+        assert_eq!(property(&form, "manual", "autoExclusive"), FALSE);
+        assert_eq!(widget(&form, "options").class, "QGroupBox");
+        assert_eq!(property(&form, "options", "title"), string("Options"));
+
+        assert_eq!(widget(&form, "link").class, "QCommandLinkButton");
+        assert_eq!(property(&form, "link", "default"), TRUE);
+        assert_eq!(widget(&form, "split").class, "QToolButton");
+        assert_eq!(property(&form, "split", "popupMode"), enumerator("QToolButton::MenuButtonPopup"));
+        assert_eq!(property(&form, "split", "autoRaise"), TRUE);
+    }
+
+    #[test]
+    fn push_like_radio_buttons_stay_exclusive() {
+        let form = form(
+            "AUTORADIOBUTTON \"A\", IDC_A, 5, 5, 40, 14, BS_PUSHLIKE | WS_GROUP\n\
+             RADIOBUTTON \"B\", IDC_B, 50, 5, 40, 14, BS_PUSHLIKE",
+        );
+        assert_eq!(widget(&form, "a").class, "QPushButton");
+        assert_eq!(property(&form, "a", "checkable"), TRUE);
+        assert_eq!(property(&form, "a", "autoExclusive"), TRUE);
+        assert_eq!(property(&form, "b", "autoExclusive"), FALSE);
+    }
+
+    fn set(flags: &str) -> Option<UiValue> {
+        Some(UiValue::Set(flags.into()))
+    }
+
+    #[test]
+    fn edits_by_style() {
+        let form = form(
+            "EDITTEXT IDC_PLAIN, 5, 5, 80, 12, ES_AUTOHSCROLL\n\
+             EDITTEXT IDC_SECRET, 5, 20, 80, 12, ES_PASSWORD | ES_RIGHT | NOT WS_BORDER\n\
+             EDITTEXT IDC_CODE, 5, 35, 80, 12, ES_NUMBER | ES_UPPERCASE | ES_READONLY | ES_CENTER\n\
+             EDITTEXT IDC_NOTES, 5, 50, 80, 40, ES_MULTILINE | WS_VSCROLL | ES_READONLY\n\
+             EDITTEXT IDC_LOG, 90, 50, 80, 40, ES_MULTILINE | ES_AUTOHSCROLL | NOT WS_BORDER",
+        );
+        assert_eq!(widget(&form, "plain").class, "QLineEdit");
+        assert_eq!(property(&form, "plain", "frame"), None);
+        assert_eq!(property(&form, "secret", "echoMode"), enumerator("QLineEdit::Password"));
+        assert_eq!(property(&form, "secret", "alignment"), set("Qt::AlignRight|Qt::AlignVCenter"));
+        assert_eq!(property(&form, "secret", "frame"), FALSE);
+        assert_eq!(property(&form, "code", "inputMethodHints"), set("Qt::ImhDigitsOnly|Qt::ImhUppercaseOnly"));
+        assert_eq!(property(&form, "code", "readOnly"), TRUE);
+        assert_eq!(property(&form, "code", "alignment"), set("Qt::AlignHCenter|Qt::AlignVCenter"));
+
+        assert_eq!(widget(&form, "notes").class, "QPlainTextEdit");
+        assert_eq!(property(&form, "notes", "readOnly"), TRUE);
+        assert_eq!(property(&form, "notes", "verticalScrollBarPolicy"), None);
+        assert_eq!(property(&form, "notes", "horizontalScrollBarPolicy"), enumerator("Qt::ScrollBarAlwaysOff"));
+        assert_eq!(property(&form, "notes", "lineWrapMode"), None);
+        assert_eq!(property(&form, "log", "lineWrapMode"), enumerator("QPlainTextEdit::NoWrap"));
+        assert_eq!(property(&form, "log", "frameShape"), enumerator("QFrame::NoFrame"));
+    }
+
+    #[test]
+    fn static_text_alignment_and_prefixes() {
+        let form = form(
+            "LTEXT \"&Name:\", IDC_NAME_LABEL, 5, 5, 50, 8\n\
+             CTEXT \"a < b\", IDC_CENTERED, 5, 15, 50, 8, SS_CENTERIMAGE\n\
+             RTEXT \"Fish && Chips\", IDC_RIGHT, 5, 25, 50, 8, SS_SUNKEN\n\
+             LTEXT \"&Raw\", IDC_RAW, 5, 35, 50, 8, SS_NOPREFIX\n\
+             CONTROL \"One line\", IDC_SIMPLE, \"Static\", SS_LEFTNOWORDWRAP, 5, 45, 50, 8",
+        );
+        assert_eq!(widget(&form, "nameLabel").class, "QLabel");
+        assert_eq!(property(&form, "nameLabel", "text"), string("Name:"));
+        assert_eq!(property(&form, "nameLabel", "alignment"), set("Qt::AlignLeft|Qt::AlignTop"));
+        assert_eq!(property(&form, "nameLabel", "wordWrap"), TRUE);
+        assert_eq!(property(&form, "centered", "alignment"), set("Qt::AlignHCenter|Qt::AlignVCenter"));
+        assert_eq!(property(&form, "centered", "textFormat"), enumerator("Qt::PlainText"));
+        assert_eq!(property(&form, "right", "text"), string("Fish & Chips"));
+        assert_eq!(property(&form, "right", "alignment"), set("Qt::AlignRight|Qt::AlignTop"));
+        assert_eq!(property(&form, "right", "frameShape"), enumerator("QFrame::Panel"));
+        assert_eq!(property(&form, "right", "frameShadow"), enumerator("QFrame::Sunken"));
+        assert_eq!(property(&form, "raw", "text"), string("&Raw"));
+        assert_eq!(property(&form, "simple", "wordWrap"), None);
+    }
+
+    #[test]
+    fn static_frames_and_lines() {
+        let form = form(
+            "CONTROL \"\", IDC_HLINE, \"Static\", SS_ETCHEDHORZ, 5, 5, 100, 1\n\
+             CONTROL \"\", IDC_VLINE, \"Static\", SS_ETCHEDVERT, 5, 10, 1, 50\n\
+             CONTROL \"\", IDC_ETCHED, \"Static\", SS_ETCHEDFRAME, 10, 10, 50, 50\n\
+             CONTROL \"\", IDC_RECT, \"Static\", SS_GRAYRECT, 70, 10, 50, 50",
+        );
+        assert_eq!(widget(&form, "hline").class, "Line");
+        assert_eq!(property(&form, "hline", "orientation"), enumerator("Qt::Horizontal"));
+        assert_eq!(property(&form, "vline", "orientation"), enumerator("Qt::Vertical"));
+        assert_eq!(widget(&form, "etched").class, "QFrame");
+        assert_eq!(property(&form, "etched", "frameShape"), enumerator("QFrame::Box"));
+        assert_eq!(property(&form, "etched", "frameShadow"), enumerator("QFrame::Sunken"));
+        assert_eq!(property(&form, "rect", "frameShape"), enumerator("QFrame::Panel"));
+        assert_eq!(property(&form, "rect", "frameShadow"), enumerator("QFrame::Plain"));
+    }
+
+    #[test]
+    fn image_statics_show_their_resource() {
+        let (form, diagnostics) = convert(
+            "IDI_APP ICON \"res\\\\app.ico\"\n\
+             1 DIALOGEX 0, 0, 100, 50\nBEGIN\n\
+             ICON IDI_APP, IDC_LOGO, 5, 5, 20, 20, SS_CENTERIMAGE\n\
+             CONTROL \"IDB_MISSING\", IDC_PICTURE, \"Static\", SS_BITMAP, 30, 5, 20, 20\n\
+             END\n",
+            &FormOptions::default(),
+        );
+        assert_eq!(
+            property(&form, "logo", "pixmap"),
+            Some(UiValue::Pixmap {
+                resource: "resources.qrc".into(),
+                path: ":/res/app.ico".into()
+            })
+        );
+        assert_eq!(property(&form, "logo", "alignment"), set("Qt::AlignCenter"));
+        assert_eq!(form.resources, ["resources.qrc"]);
+        assert_eq!(property(&form, "picture", "pixmap"), None);
+        assert!(diagnostics.render().contains("no BITMAP resource 'IDB_MISSING'"));
+    }
+
+    fn height(form: &UiForm, name: &str) -> i32 {
+        match property(form, name, "geometry") {
+            Some(UiValue::Rect { height, .. }) => height,
+            other => panic!("no geometry: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn combo_boxes_take_the_height_of_their_field() {
+        let form = form(
+            "COMBOBOX IDC_COLOR, 5, 5, 80, 60, CBS_DROPDOWNLIST | CBS_SORT\n\
+             COMBOBOX IDC_FREE, 5, 25, 80, 12, CBS_DROPDOWN",
+        );
+        assert_eq!(widget(&form, "color").class, "QComboBox");
+        assert_eq!(property(&form, "color", "editable"), None);
+        assert_eq!(property(&form, "color", "insertPolicy"), enumerator("QComboBox::InsertAlphabetically"));
+        assert_eq!(height(&form, "color"), 28);
+        assert_eq!(property(&form, "color", "maxVisibleItems"), Some(UiValue::Number(5)));
+        assert_eq!(property(&form, "free", "editable"), TRUE);
+        assert_eq!(height(&form, "free"), 24);
+    }
+
+    #[test]
+    fn list_boxes_by_style() {
+        let form = form(
+            "LISTBOX IDC_PLAIN, 5, 5, 80, 45\n\
+             LISTBOX IDC_MANY, 90, 5, 80, 45, LBS_EXTENDEDSEL | LBS_NOINTEGRALHEIGHT | WS_VSCROLL\n\
+             LISTBOX IDC_COLUMNS, 5, 55, 80, 40, LBS_MULTICOLUMN | LBS_SORT | LBS_DISABLENOSCROLL | NOT WS_BORDER\n\
+             LISTBOX IDC_NONE, 90, 55, 80, 40, LBS_NOSEL | LBS_MULTIPLESEL",
+        );
+        assert_eq!(widget(&form, "plain").class, "QListWidget");
+        // Five whole 16 pixel items within the 90 pixels, with the border.
+        assert_eq!(height(&form, "plain"), 84);
+        assert_eq!(property(&form, "plain", "selectionMode"), None);
+        assert_eq!(property(&form, "plain", "verticalScrollBarPolicy"), enumerator("Qt::ScrollBarAlwaysOff"));
+        assert_eq!(height(&form, "many"), 90);
+        assert_eq!(property(&form, "many", "selectionMode"), enumerator("QAbstractItemView::ExtendedSelection"));
+        assert_eq!(property(&form, "many", "verticalScrollBarPolicy"), None);
+        assert_eq!(property(&form, "columns", "flow"), enumerator("QListView::TopToBottom"));
+        assert_eq!(property(&form, "columns", "sortingEnabled"), TRUE);
+        assert_eq!(property(&form, "columns", "verticalScrollBarPolicy"), enumerator("Qt::ScrollBarAlwaysOn"));
+        assert_eq!(property(&form, "columns", "frameShape"), enumerator("QFrame::NoFrame"));
+        assert_eq!(property(&form, "none", "selectionMode"), enumerator("QAbstractItemView::NoSelection"));
+    }
+
+    #[test]
+    fn common_controls_by_window_class() {
+        let form = form(
+            "CONTROL \"\", IDC_REPORT, \"SysListView32\", LVS_REPORT | LVS_NOCOLUMNHEADER | LVS_SINGLESEL, 5, 5, 80, 40\n\
+             CONTROL \"\", IDC_ICONS, WC_LISTVIEW, LVS_ICON | LVS_EDITLABELS, 90, 5, 80, 40\n\
+             CONTROL \"\", IDC_TREE, \"systreeview32\", TVS_LINESATROOT, 5, 50, 80, 40\n\
+             CONTROL \"\", IDC_TABS, \"SysTabControl32\", TCS_BOTTOM, 90, 50, 80, 40\n\
+             CONTROL \"\", IDC_BUSY, \"msctls_progress32\", PBS_MARQUEE | PBS_VERTICAL, 5, 95, 10, 40\n\
+             CONTROL \"\", IDC_VOLUME, \"msctls_trackbar32\", TBS_VERT | TBS_BOTH | TBS_AUTOTICKS, 20, 95, 20, 40\n\
+             CONTROL \"\", IDC_TIME, \"SysDateTimePick32\", DTS_TIMEFORMAT, 45, 95, 50, 12\n\
+             CONTROL \"\", IDC_DATE, \"SysDateTimePick32\", DTS_SHORTDATEFORMAT, 45, 110, 50, 12\n\
+             CONTROL \"\", IDC_ADDRESS, \"SysIPAddress32\", WS_TABSTOP, 100, 95, 60, 12\n\
+             CONTROL \"See <a>help</a>\", IDC_HELP_LINK, \"SysLink\", 0, 100, 110, 60, 10\n\
+             CONTROL \"\", IDC_RICH, \"RichEdit20W\", ES_READONLY | WS_VSCROLL, 100, 125, 60, 20",
+        );
+        assert_eq!(widget(&form, "report").class, "QTreeWidget");
+        assert_eq!(property(&form, "report", "rootIsDecorated"), FALSE);
+        let header = widget(&form, "report").attributes.iter().find(|a| a.name == "headerVisible");
+        assert_eq!(header.map(|a| &a.value), Some(&UiValue::Bool(false)));
+        assert_eq!(property(&form, "report", "selectionMode"), enumerator("QAbstractItemView::SingleSelection"));
+        assert_eq!(widget(&form, "icons").class, "QListWidget");
+        assert_eq!(property(&form, "icons", "viewMode"), enumerator("QListView::IconMode"));
+        assert_eq!(
+            property(&form, "icons", "editTriggers"),
+            set("QAbstractItemView::EditKeyPressed|QAbstractItemView::SelectedClicked")
+        );
+        assert_eq!(widget(&form, "tree").class, "QTreeWidget");
+        assert_eq!(property(&form, "tree", "rootIsDecorated"), None);
+        assert_eq!(widget(&form, "tabs").class, "QTabWidget");
+        assert_eq!(property(&form, "tabs", "tabPosition"), enumerator("QTabWidget::South"));
+
+        assert_eq!(widget(&form, "busy").class, "QProgressBar");
+        assert_eq!(property(&form, "busy", "maximum"), Some(UiValue::Number(0)));
+        assert_eq!(property(&form, "busy", "orientation"), enumerator("Qt::Vertical"));
+        assert_eq!(widget(&form, "volume").class, "QSlider");
+        assert_eq!(property(&form, "volume", "tickPosition"), enumerator("QSlider::TicksBothSides"));
+        assert_eq!(property(&form, "volume", "tickInterval"), Some(UiValue::Number(1)));
+
+        assert_eq!(widget(&form, "time").class, "QTimeEdit");
+        assert_eq!(widget(&form, "date").class, "QDateEdit");
+        assert_eq!(property(&form, "date", "calendarPopup"), TRUE);
+        assert_eq!(property(&form, "address", "inputMask"), string("000.000.000.000;_"));
+        assert_eq!(property(&form, "helpLink", "text"), string("See <a href=\"#\">help</a>"));
+        assert_eq!(property(&form, "helpLink", "textFormat"), enumerator("Qt::RichText"));
+        assert_eq!(widget(&form, "rich").class, "QTextEdit");
+        assert_eq!(property(&form, "rich", "readOnly"), TRUE);
+    }
+
+    #[test]
+    fn lone_up_down_is_a_spin_box() {
+        let form = form("CONTROL \"\", IDC_COUNT, \"msctls_updown32\", UDS_WRAP | UDS_SETBUDDYINT, 5, 5, 10, 14");
+        assert_eq!(widget(&form, "count").class, "QSpinBox");
+        assert_eq!(property(&form, "count", "maximum"), Some(UiValue::Number(100)));
+        assert_eq!(property(&form, "count", "wrapping"), TRUE);
+        assert_eq!(property(&form, "count", "groupSeparatorShown"), TRUE);
+    }
+
+    #[test]
+    fn edit_and_up_down_buddy_become_one_spin_box() {
+        let form = form(
+            "EDITTEXT IDC_COUNT, 10, 10, 40, 12, ES_RIGHT | ES_READONLY | NOT WS_BORDER\n\
+             CONTROL \"\", IDC_COUNT_SPIN, \"msctls_updown32\", UDS_AUTOBUDDY | UDS_WRAP, 50, 10, 10, 12",
+        );
+        assert_eq!(form.widget.children.len(), 1);
+        let spin = widget(&form, "count");
+        assert_eq!(spin.class, "QSpinBox");
+        assert_eq!(spin.get("geometry"), Some(&UiValue::Rect { x: 20, y: 20, width: 100, height: 24 }));
+        assert_eq!(property(&form, "count", "wrapping"), TRUE);
+        assert_eq!(property(&form, "count", "alignment"), set("Qt::AlignRight|Qt::AlignVCenter"));
+        assert_eq!(property(&form, "count", "readOnly"), TRUE);
+        assert_eq!(property(&form, "count", "frame"), FALSE);
+        assert!(spin.comment.as_deref().unwrap().contains("UDS_ARROWKEYS"));
+    }
+
+    #[test]
+    fn aligned_up_down_keeps_the_edit_area() {
+        let form = form(
+            "EDITTEXT IDC_COUNT, 10, 10, 40, 12\n\
+             CONTROL \"\", IDC_COUNT_SPIN, \"msctls_updown32\", UDS_AUTOBUDDY | UDS_ALIGNRIGHT | UDS_ARROWKEYS, 0, 0, 10, 12",
+        );
+        let spin = widget(&form, "count");
+        assert_eq!(spin.get("geometry"), Some(&UiValue::Rect { x: 20, y: 20, width: 80, height: 24 }));
+        assert_eq!(spin.comment, None);
+        assert_eq!(property(&form, "count", "frame"), None);
+    }
+
+    #[test]
+    fn up_down_without_a_buddy_stays_apart() {
+        let form = form(
+            "EDITTEXT IDC_COUNT, 10, 10, 40, 12\n\
+             CONTROL \"\", IDC_FAR_SPIN, \"msctls_updown32\", UDS_AUTOBUDDY, 150, 60, 10, 12\n\
+             EDITTEXT IDC_OTHER, 10, 30, 40, 12\n\
+             CONTROL \"\", IDC_OTHER_SPIN, \"msctls_updown32\", UDS_ARROWKEYS, 50, 30, 10, 12",
+        );
+        assert_eq!(widget(&form, "count").class, "QLineEdit");
+        assert_eq!(widget(&form, "farSpin").class, "QSpinBox");
+        assert_eq!(widget(&form, "other").class, "QLineEdit");
+        assert_eq!(widget(&form, "otherSpin").class, "QSpinBox");
+    }
+
+    #[test]
+    fn window_styles_of_controls() {
+        let form = form(
+            "EDITTEXT IDC_NAME, 5, 5, 100, 12, WS_DISABLED, WS_EX_CLIENTEDGE\n\
+             EDITTEXT IDC_HIDDEN, 5, 20, 100, 12, NOT WS_VISIBLE | NOT WS_BORDER\n\
+             EDITTEXT IDC_CLIENT, 5, 35, 100, 12, NOT WS_BORDER, WS_EX_CLIENTEDGE\n\
+             LTEXT \"Sunken\", IDC_SUNKEN, 5, 50, 60, 8, 0, WS_EX_CLIENTEDGE\n\
+             LTEXT \"Edge\", IDC_EDGE, 5, 60, 60, 8, 0, WS_EX_STATICEDGE | WS_EX_LAYOUTRTL",
+        );
+        assert_eq!(property(&form, "name", "enabled"), FALSE);
+        assert_eq!(property(&form, "name", "visible"), None);
+        assert_eq!(property(&form, "hidden", "visible"), FALSE);
+        assert_eq!(property(&form, "hidden", "frame"), FALSE);
+        assert_eq!(property(&form, "client", "frame"), None);
+        assert_eq!(property(&form, "sunken", "frameShape"), enumerator("QFrame::StyledPanel"));
+        assert_eq!(property(&form, "sunken", "frameShadow"), enumerator("QFrame::Sunken"));
+        assert_eq!(property(&form, "edge", "frameShape"), enumerator("QFrame::Panel"));
+        assert_eq!(property(&form, "edge", "layoutDirection"), enumerator("Qt::RightToLeft"));
+    }
+
+    // A DLGINIT entry carrying `text`, in resource script syntax.
+    fn init_entry(control: &str, message: u32, text: &str) -> String {
+        format!("{}, {:#x}, {}, 0\n\"{}\"\n", control, message, text.len(), text.replace('"', "\"\""))
+    }
+
+    fn convert_with_init(controls: &str, entries: &[String]) -> UiForm {
+        let script = format!(
+            "#define IDC_FIRST 1001\n#define IDC_SECOND 1002\n\
+             1 DIALOGEX 0, 0, 200, 100\nBEGIN\n{}\nEND\n1 DLGINIT\nBEGIN\n{}0\nEND\n",
+            controls,
+            entries.concat()
+        );
+        let (form, diagnostics) = convert(&script, &FormOptions::default());
+        assert!(!diagnostics.has_errors(), "{}", diagnostics.render());
+        form
+    }
+
+    #[test]
+    fn dialog_init_fills_combo_and_list_boxes() {
+        let form = convert_with_init(
+            "COMBOBOX IDC_FIRST, 5, 5, 80, 60, CBS_DROPDOWNLIST\nLISTBOX IDC_SECOND, 90, 5, 80, 60",
+            &[
+                init_entry("IDC_FIRST", 0x403, "Red"),
+                init_entry("IDC_FIRST", 0x403, "Blue"),
+                init_entry("IDC_SECOND", 0x180, "One"),
+            ],
+        );
+        assert_eq!(widget(&form, "first").items, ["Red", "Blue"]);
+        assert_eq!(widget(&form, "second").items, ["One"]);
+    }
+
+    #[test]
+    fn mfc_link_escapes_its_url_and_caption() {
+        let form = convert_with_init(
+            "CONTROL \"Fish && <Chips>\", IDC_FIRST, \"MfcLink\", WS_TABSTOP, 5, 5, 80, 10",
+            &[init_entry(
+                "IDC_FIRST",
+                0x37C,
+                "<MFCLink_Url>a.html?x=\"1\"&y=2</MFCLink_Url><MFCLink_UrlPrefix>https://example.com/</MFCLink_UrlPrefix>",
+            )],
+        );
+        assert_eq!(
+            property(&form, "first", "text"),
+            string("<a href=\"https://example.com/a.html?x=&quot;1&quot;&amp;y=2\">Fish &amp; &lt;Chips&gt;</a>")
+        );
+        assert_eq!(property(&form, "first", "openExternalLinks"), TRUE);
+    }
+
+    #[test]
+    fn mfc_controls() {
+        let form = convert_with_init(
+            "CONTROL \"Save\", IDC_FIRST, \"MfcButton\", WS_TABSTOP, 5, 5, 50, 14\n\
+             CONTROL \"\", IDC_SECOND, \"MfcEditBrowse\", WS_BORDER | WS_TABSTOP, 5, 25, 80, 12\n\
+             CONTROL \"Colour\", IDC_COLOUR, \"MfcColorButton\", WS_TABSTOP, 5, 45, 50, 14\n\
+             CONTROL \"\", IDC_FONT, \"MfcFontComboBox\", CBS_DROPDOWNLIST, 5, 65, 80, 60",
+            &[
+                init_entry("IDC_FIRST", 0x37C, "<MFCButton_TooltipText>Save it</MFCButton_TooltipText>"),
+                init_entry("IDC_SECOND", 0x37C, "<MFCEditBrowse_Mode>2</MFCEditBrowse_Mode>"),
+            ],
+        );
+        assert_eq!(widget(&form, "first").class, "QPushButton");
+        assert_eq!(property(&form, "first", "toolTip"), string("Save it"));
+        assert_eq!(widget(&form, "second").class, "QLineEdit");
+        assert!(widget(&form, "second").comment.as_ref().unwrap().contains("getExistingDirectory"));
+        assert_eq!(widget(&form, "colour").class, "ColorButton");
+        assert_eq!(form.custom_widgets[0].extends, "QToolButton");
+        assert_eq!(widget(&form, "font").class, "QFontComboBox");
+        assert_eq!(property(&form, "font", "fontFilters"), set("QFontComboBox::ScalableFonts"));
+    }
+
+    #[test]
+    fn unknown_window_classes_are_promoted() {
+        let options = FormOptions {
+            promotion: PromotionRule {
+                class: "Q{class}".into(),
+                header: "widgets/{lower}.h".into(),
+            },
+            ..FormOptions::default()
+        };
+        let (form, _) = convert(
+            "1 DIALOGEX 0, 0, 200, 100\nBEGIN\n\
+             CONTROL \"\", IDC_CHART, \"Chart\", WS_BORDER, 5, 5, 80, 40\n\
+             CONTROL \"\", IDC_OTHER_CHART, \"Chart\", 0, 90, 5, 80, 40\n\
+             CONTROL \"\", IDC_GAUGE, \"My.Gauge\", 0, 5, 50, 80, 40\n\
+             END\n",
+            &options,
+        );
+        assert_eq!(widget(&form, "chart").class, "QChart");
+        assert_eq!(property(&form, "chart", "frameShape"), enumerator("QFrame::Box"));
+        assert_eq!(widget(&form, "chart").comment.as_deref(), Some("TODO: port the \"Chart\" window class"));
+        assert_eq!(widget(&form, "otherChart").class, "QChart");
+        assert_eq!(widget(&form, "gauge").class, "QMy_Gauge");
+
+        let custom: Vec<_> = form.custom_widgets.iter().map(|c| (c.class.as_str(), c.extends.as_str(), c.header.as_str())).collect();
+        assert_eq!(
+            custom,
+            [("QChart", "QFrame", "widgets/qchart.h"), ("QMy_Gauge", "QWidget", "widgets/qmy_gauge.h")]
+        );
+    }
+
+    #[test]
+    fn promoted_class_names_are_identifiers() {
+        let rule = PromotionRule::default();
+        assert_eq!(rule.class_name("\"Chart\""), "Chart");
+        assert_eq!(rule.class_name("3D"), "Custom3D");
+        assert_eq!(rule.header("Chart"), "chart.h");
+    }
+}